@@ -0,0 +1,401 @@
+// Command-line interface to the translation engine, for terminals and scripts:
+//
+//     packswitcher translate --to pnpm "npm i -D vitest"
+//     echo "npm i -D vitest" | packswitcher translate --to bun
+//     packswitcher rewrite --to pnpm --check docs/
+//
+// Translations use the custom rules of the menubar app, so both give the same results.
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use packswitcher_core::{PackageManager, Rule, TranslationResult, YarnDialect};
+use similar::TextDiff;
+
+#[derive(Parser)]
+#[command(
+    name = "packswitcher",
+    version,
+    about = "Translates package manager commands between npm, pnpm, Yarn, Bun and Deno"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Translate a command, or the text on stdin when no command is given
+    ///
+    /// Exits with 0 when the text was translated, 3 when there was nothing to translate and
+    /// 4 when the translation lost something, like a flag the target has no equivalent for.
+    Translate(TranslateArgs),
+    /// Translate the commands in the code blocks and inline code of Markdown and text files
+    ///
+    /// Directories are searched for .md, .mdx, .markdown and .txt files. Files are rewritten
+    /// in place unless --check or --diff is given.
+    Rewrite(RewriteArgs),
+}
+
+// The options of every translation
+#[derive(Args)]
+struct TranslationOptions {
+    /// The package manager to translate for
+    #[arg(long, value_name = "PM")]
+    to: PackageManager,
+
+    /// The Yarn version to translate for: classic (Yarn 1) or berry (Yarn 2+)
+    #[arg(long, value_name = "VERSION", default_value = "classic", value_parser = parse_yarn_dialect)]
+    yarn: YarnDialect,
+
+    /// Read the rules from rules.toml or rules.json in this directory instead of the app's
+    /// config directory
+    #[arg(long, value_name = "DIR")]
+    rules_dir: Option<PathBuf>,
+
+    /// Don't use any custom rules
+    #[arg(long, conflicts_with = "rules_dir")]
+    no_rules: bool,
+}
+
+#[derive(Args)]
+struct TranslateArgs {
+    #[command(flatten)]
+    options: TranslationOptions,
+
+    /// Print the translation, how it was made and its warnings as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// The command to translate, best quoted as one argument
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    command: Vec<String>,
+}
+
+#[derive(Args)]
+struct RewriteArgs {
+    #[command(flatten)]
+    options: TranslationOptions,
+
+    /// Don't write anything, and exit with 1 if any file would change
+    #[arg(long)]
+    check: bool,
+
+    /// Print the changes as a unified diff instead of writing them
+    #[arg(long)]
+    diff: bool,
+
+    /// The files and directories to rewrite
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+}
+
+// How a translation went, reported with the exit code and in the JSON output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Translated,
+    Unchanged,
+    Lossy,
+}
+
+impl Status {
+    fn exit_code(self) -> u8 {
+        match self {
+            Status::Translated => 0,
+            Status::Unchanged => 3,
+            Status::Lossy => 4,
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct JsonOutput {
+    status: Status,
+    #[serde(flatten)]
+    result: TranslationResult,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Translate(args) => translate(args),
+        Command::Rewrite(args) => rewrite(args),
+    };
+
+    match result {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn translate(args: TranslateArgs) -> Result<u8, String> {
+    let options = &args.options;
+    let rules = options.load_rules()?;
+
+    // Text from stdin is written back exactly, while a command from the arguments gets a
+    // line of its own
+    let from_stdin = args.command.is_empty();
+    let text = if from_stdin {
+        let mut text = String::new();
+        io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("Failed to read stdin: {}", e))?;
+        text
+    } else {
+        args.command.join(" ")
+    };
+
+    let (status, result) = translate_text(&text, options.to, options.yarn, &rules);
+    if args.json {
+        let output = JsonOutput { status, result };
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    } else {
+        for warning in &result.warnings {
+            eprintln!("warning: {}", warning);
+        }
+        if from_stdin {
+            print!("{}", result.translated);
+        } else {
+            println!("{}", result.translated);
+        }
+    }
+
+    Ok(status.exit_code())
+}
+
+fn rewrite(args: RewriteArgs) -> Result<u8, String> {
+    let options = &args.options;
+    let rules = options.load_rules()?;
+    let documents = packswitcher_core::find_documents(&args.paths)?;
+
+    let mut changed = 0;
+    let mut failed = false;
+    for path in &documents {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("error: Failed to read {}: {}", path.display(), e);
+                failed = true;
+                continue;
+            }
+        };
+        let Some(result) =
+            packswitcher_core::translate_document(&text, options.to, options.yarn, &rules)
+        else {
+            continue;
+        };
+        changed += 1;
+
+        for warning in &result.warnings {
+            eprintln!("warning: {}: {}", path.display(), warning);
+        }
+        if args.diff {
+            print!("{}", unified_diff(path, &text, &result.translated));
+        }
+        if args.check {
+            eprintln!("Would rewrite {}", path.display());
+        } else if !args.diff {
+            match fs::write(path, &result.translated) {
+                Ok(()) => eprintln!("Rewrote {}", path.display()),
+                Err(e) => {
+                    eprintln!("error: Failed to write {}: {}", path.display(), e);
+                    failed = true;
+                }
+            }
+        }
+    }
+
+    let verb = if args.check || args.diff {
+        "would change"
+    } else {
+        "changed"
+    };
+    eprintln!("{} of {} files {}", changed, documents.len(), verb);
+
+    Ok(if failed || (args.check && changed > 0) {
+        1
+    } else {
+        0
+    })
+}
+
+fn unified_diff(path: &Path, original: &str, rewritten: &str) -> String {
+    let path = path.display().to_string();
+    TextDiff::from_lines(original, rewritten)
+        .unified_diff()
+        .header(&path, &path)
+        .to_string()
+}
+
+// Translates `text` like the menubar app does. Text without anything to translate comes
+// back as it was.
+fn translate_text(
+    text: &str,
+    to_pm: PackageManager,
+    yarn_dialect: YarnDialect,
+    rules: &[Rule],
+) -> (Status, TranslationResult) {
+    match packswitcher_core::translate(text, to_pm, yarn_dialect, rules) {
+        Some(result) if result.warnings.is_empty() => (Status::Translated, result),
+        Some(result) => (Status::Lossy, result),
+        None => (
+            Status::Unchanged,
+            TranslationResult {
+                original: text.to_string(),
+                translated: text.to_string(),
+                from: Vec::new(),
+                to: to_pm,
+                rules: Vec::new(),
+                warnings: Vec::new(),
+            },
+        ),
+    }
+}
+
+impl TranslationOptions {
+    fn load_rules(&self) -> Result<Vec<Rule>, String> {
+        if self.no_rules {
+            return Ok(Vec::new());
+        }
+        match self.rules_dir.clone().or_else(app_config_dir) {
+            Some(dir) => packswitcher_core::load_rules(&dir),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+// Where the menubar app keeps its settings and rules
+fn app_config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("com.packswitcher.app"))
+}
+
+fn parse_yarn_dialect(value: &str) -> Result<YarnDialect, String> {
+    match value {
+        "classic" => Ok(YarnDialect::Classic),
+        "berry" => Ok(YarnDialect::Berry),
+        _ => Err("expected classic or berry".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_text() {
+        let translate = |text: &str, to_pm| translate_text(text, to_pm, YarnDialect::Classic, &[]);
+
+        let (status, result) = translate("npm i -D vitest", PackageManager::Pnpm);
+        assert_eq!(status, Status::Translated);
+        assert_eq!(result.translated, "pnpm add -D vitest");
+
+        let (status, result) = translate("yarn add -T lodash", PackageManager::Npm);
+        assert_eq!(status, Status::Lossy);
+        assert_eq!(result.translated, "npm install lodash");
+
+        let (status, result) = translate("echo done\n", PackageManager::Bun);
+        assert_eq!(status, Status::Unchanged);
+        assert_eq!(result.translated, "echo done\n");
+    }
+
+    #[test]
+    fn test_json_output() {
+        let (status, result) =
+            translate_text("npx tsc", PackageManager::Bun, YarnDialect::Classic, &[]);
+        let output = serde_json::to_value(JsonOutput { status, result }).unwrap();
+        assert_eq!(
+            output,
+            serde_json::json!({
+                "status": "translated",
+                "original": "npx tsc",
+                "translated": "bunx tsc",
+                "from": ["npm"],
+                "to": "bun",
+                "rules": ["dlx"],
+                "warnings": [],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let cli = Cli::try_parse_from([
+            "packswitcher",
+            "translate",
+            "--to",
+            "yarn",
+            "--yarn",
+            "berry",
+            "npm",
+            "i",
+            "-D",
+            "vitest",
+        ])
+        .unwrap();
+        let Command::Translate(args) = cli.command else {
+            panic!("expected the translate command");
+        };
+        assert_eq!(args.options.to, PackageManager::Yarn);
+        assert_eq!(args.options.yarn, YarnDialect::Berry);
+        assert_eq!(args.command, ["npm", "i", "-D", "vitest"]);
+
+        assert!(Cli::try_parse_from(["packswitcher", "translate", "--to", "pip"]).is_err());
+        assert!(Cli::try_parse_from(["packswitcher", "translate", "npm i"]).is_err());
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let dir = std::env::temp_dir().join(format!("packswitcher-rewrite-{}", std::process::id()));
+        let guide = dir.join("docs/guide.md");
+        fs::create_dir_all(guide.parent().unwrap()).unwrap();
+        fs::write(
+            &guide,
+            "Run `npm install` first.\n\n```sh\nnpm i -D vitest\n```\n",
+        )
+        .unwrap();
+        fs::write(dir.join("docs/other.md"), "Nothing to see here.\n").unwrap();
+
+        let args = |check, diff| RewriteArgs {
+            options: TranslationOptions {
+                to: PackageManager::Pnpm,
+                yarn: YarnDialect::Classic,
+                rules_dir: None,
+                no_rules: true,
+            },
+            check,
+            diff,
+            paths: vec![dir.clone()],
+        };
+
+        // Checking and diffing leave the files alone
+        assert_eq!(rewrite(args(true, false)), Ok(1));
+        assert_eq!(rewrite(args(false, true)), Ok(0));
+        assert!(fs::read_to_string(&guide)
+            .unwrap()
+            .contains("npm i -D vitest"));
+
+        assert_eq!(rewrite(args(false, false)), Ok(0));
+        assert_eq!(
+            fs::read_to_string(&guide).unwrap(),
+            "Run `pnpm install` first.\n\n```sh\npnpm add -D vitest\n```\n"
+        );
+        assert_eq!(rewrite(args(true, false)), Ok(0));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        assert_eq!(
+            unified_diff(Path::new("README.md"), "# App\nnpm ci\n", "# App\npnpm install --frozen-lockfile\n"),
+            "--- README.md\n+++ README.md\n@@ -1,2 +1,2 @@\n # App\n-npm ci\n+pnpm install --frozen-lockfile\n"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+// Finding the Markdown and text documents to translate among the files and directories a
+// user points at.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// The documents looked for in directories, by extension
+const DOCUMENT_EXTENSIONS: &[&str] = &["md", "mdx", "markdown", "txt"];
+
+// Directories with installed packages rather than a project's own documents
+const SKIPPED_DIRS: &[&str] = &["node_modules"];
+
+/// Returns the documents to translate among `paths`. Files are taken as they are, whatever
+/// their extension, and directories are searched for `.md`, `.mdx`, `.markdown` and `.txt`
+/// files, skipping hidden directories, `node_modules` and symbolic links to directories.
+/// The files of a directory are returned sorted by path.
+pub fn find_documents(paths: &[PathBuf]) -> Result<Vec<PathBuf>, String> {
+    let mut documents = Vec::new();
+    for path in paths {
+        let metadata =
+            fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if metadata.is_dir() {
+            find_in_dir(path, &mut documents)?;
+        } else {
+            documents.push(path.clone());
+        }
+    }
+    Ok(documents)
+}
+
+fn find_in_dir(dir: &Path, documents: &mut Vec<PathBuf>) -> Result<(), String> {
+    let mut entries = fs::read_dir(dir)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    entries.sort_by_key(|entry| entry.path());
+
+    for entry in entries {
+        let path = entry.path();
+        let file_type = entry
+            .file_type()
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+
+        if file_type.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                find_in_dir(&path, documents)?;
+            }
+        } else if !path.is_dir() && is_document(&path) {
+            documents.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_document(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            DOCUMENT_EXTENSIONS
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(extension))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_documents() {
+        let dir =
+            std::env::temp_dir().join(format!("packswitcher-documents-{}", std::process::id()));
+        for file in [
+            "README.md",
+            "docs/guide.mdx",
+            "docs/setup/install.markdown",
+            "docs/notes.TXT",
+            "docs/logo.png",
+            "docs/.vitepress/config.md",
+            "node_modules/react/README.md",
+            "script.sh",
+        ] {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        assert_eq!(
+            find_documents(&[
+                dir.join("docs"),
+                dir.join("script.sh"),
+                dir.join("README.md")
+            ]),
+            Ok(vec![
+                dir.join("docs/guide.mdx"),
+                dir.join("docs/notes.TXT"),
+                dir.join("docs/setup/install.markdown"),
+                dir.join("script.sh"),
+                dir.join("README.md"),
+            ])
+        );
+        assert!(find_documents(&[dir.join("missing")]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,546 @@
+// Intermediate representation of a package manager command.
+//
+// `parse` turns a command written for any supported package manager into a `Command`,
+// and `render` writes a `Command` out for another one. Everything a translation needs to
+// know lives here, so the two sides can be tested on their own.
+
+use crate::package_manager::PackageManager::{self, Bun, Deno, Npm, Pnpm, Yarn};
+use crate::shell::Token;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    // The package manager the command was written for
+    pub pm: PackageManager,
+    // The workspaces the command applies to, if it selects any
+    pub scope: Option<WorkspaceScope>,
+    pub operation: Operation,
+    // Redirections like `> /dev/null`, kept as written after the command
+    pub redirections: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    // Install the dependencies of the project; a frozen install fails instead of
+    // updating the lockfile (`npm ci`)
+    Install {
+        frozen: bool,
+        args: Vec<Token>,
+    },
+    // Add packages to the project, or install them globally
+    Add {
+        args: Vec<Arg>,
+        global: bool,
+    },
+    // Remove packages from the project, or uninstall them globally
+    Remove {
+        args: Vec<Arg>,
+        global: bool,
+    },
+    // Update dependencies within their ranges
+    Update {
+        args: Vec<Token>,
+    },
+    // Run a package.json script. `options` are the package manager's own options before
+    // the script name, `args` are passed on to the script.
+    Run {
+        invocation: Invocation,
+        options: Vec<Token>,
+        script: Option<Token>,
+        args: Vec<Token>,
+    },
+    // Run a binary of an installed package: `npm exec`, `pnpm exec`, `yarn exec`
+    Exec {
+        args: Vec<Token>,
+    },
+    // Download and run a package: `npx`, `pnpm dlx`, `yarn dlx`, `bunx`, `deno run npm:`
+    Dlx {
+        // `--yes`: install without asking first
+        yes: bool,
+        // Other runner options, with the token each was written as
+        flags: Vec<(RunnerFlag, Token)>,
+        // Packages to install for the command: `npx -p typescript tsc`
+        packages: Vec<Token>,
+        // A command for the shell to run with the packages installed: `npx -c 'eslint .'`
+        shell_command: Option<Token>,
+        // The package to run and its arguments
+        args: Vec<Token>,
+    },
+    // Scaffold a project: `npm create vite@latest my-app`. The initializer is spelled the
+    // way `create` takes it (`vite@latest`, `@scope/app`).
+    Create {
+        initializer: String,
+        args: Vec<Token>,
+    },
+    Publish {
+        args: Vec<Token>,
+    },
+    // Any other subcommand, kept as written
+    Other {
+        subcommand: Token,
+        args: Vec<Token>,
+    },
+}
+
+impl Operation {
+    // A short name for the operation, to tell the user how a command was understood
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Install { frozen: true, .. } => "frozen install",
+            Operation::Install { .. } => "install",
+            Operation::Add { global: true, .. } => "global add",
+            Operation::Add { .. } => "add",
+            Operation::Remove { global: true, .. } => "global remove",
+            Operation::Remove { .. } => "remove",
+            Operation::Update { .. } => "update",
+            Operation::Run { .. } => "run",
+            Operation::Exec { .. } => "exec",
+            Operation::Dlx { .. } => "dlx",
+            Operation::Create { .. } => "create",
+            Operation::Publish { .. } => "publish",
+            Operation::Other { .. } => "other",
+        }
+    }
+}
+
+// How a script was invoked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invocation {
+    // `npm run build`
+    Run,
+    // npm's lifecycle shortcuts: `npm test`, `npm start`
+    Shortcut,
+    // Yarn runs any script by name: `yarn build`
+    Bare,
+}
+
+// An argument of `add` or `remove`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    Package(PackageSpec),
+    // A dependency-type flag, with the token it was written as
+    Dependency(DependencyFlag, Token),
+    // Any other option or option value, kept as written
+    Other(Token),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSpec {
+    pub registry: Registry,
+    // The spec as written, without a `npm:` / `jsr:` registry prefix
+    pub token: Token,
+    pub source: SpecSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Registry {
+    Npm,
+    Jsr,
+}
+
+// Where a package spec gets the package from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecSource {
+    // `react`, `react@^18`, `@types/node`
+    Registry,
+    // `my-react@npm:react@18` installs `react@18` under the name `my-react`
+    Alias { name: String, target: String },
+    // `github:user/repo#branch`, `user/repo`, `git+https://github.com/user/repo.git`
+    Git,
+    // `./pkg.tgz`, `https://example.com/pkg.tgz`
+    Tarball,
+    // `file:../lib`, `../lib`
+    Path,
+    // `link:../lib` symlinks a directory. Bun also links packages registered with
+    // `bun link` by name: `link:my-lib`.
+    Link { target: String },
+    // `my-lib@workspace:*` is a package of the same workspace
+    Workspace { name: String },
+}
+
+impl PackageSpec {
+    pub fn new(registry: Registry, token: Token) -> Self {
+        PackageSpec {
+            registry,
+            source: parse_spec_source(&token.value),
+            token,
+        }
+    }
+}
+
+// Git hosts and protocols npm accepts in front of a repository
+const GIT_PREFIXES: &[&str] = &[
+    "git+",
+    "git:",
+    "git@",
+    "github:",
+    "gitlab:",
+    "bitbucket:",
+    "gist:",
+];
+
+pub fn parse_spec_source(spec: &str) -> SpecSource {
+    if let Some(target) = spec.strip_prefix("link:") {
+        return SpecSource::Link {
+            target: target.to_string(),
+        };
+    }
+    if spec.ends_with(".tgz") || spec.ends_with(".tar.gz") || spec.ends_with(".tar") {
+        return SpecSource::Tarball;
+    }
+    if spec.starts_with("file:") || is_path(spec) {
+        return SpecSource::Path;
+    }
+    if GIT_PREFIXES.iter().any(|prefix| spec.starts_with(prefix)) {
+        return SpecSource::Git;
+    }
+    if spec.starts_with("http://") || spec.starts_with("https://") {
+        // npm reads other URLs as tarballs
+        return match spec.contains(".git#") || spec.ends_with(".git") {
+            true => SpecSource::Git,
+            false => SpecSource::Tarball,
+        };
+    }
+    // GitHub's `user/repo` shorthand
+    if !spec.starts_with('@') && spec.contains('/') && !spec.contains(':') {
+        return SpecSource::Git;
+    }
+    if spec.is_empty() || !spec.starts_with(|c: char| c == '@' || c.is_ascii_alphanumeric()) {
+        return SpecSource::Registry;
+    }
+
+    // A named spec: `name@<version or any of the above>`
+    match split_version(spec) {
+        (name, Some(version)) => match version.strip_prefix("npm:") {
+            Some(target) => SpecSource::Alias {
+                name: name.to_string(),
+                target: target.to_string(),
+            },
+            None if version.starts_with("workspace:") => SpecSource::Workspace {
+                name: name.to_string(),
+            },
+            None if version.contains(':') || is_path(version) => parse_spec_source(version),
+            None => SpecSource::Registry,
+        },
+        (_, None) => SpecSource::Registry,
+    }
+}
+
+// `./lib`, `../lib`, `/abs/lib`, `~/lib`
+pub fn is_path(spec: &str) -> bool {
+    spec == "."
+        || spec == ".."
+        || ["./", "../", "/", "~/"]
+            .iter()
+            .any(|prefix| spec.starts_with(prefix))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceScope {
+    // Specific workspaces, by package name or by path
+    Named(Vec<String>),
+    // Every workspace of the project
+    All,
+    // The workspace root
+    Root,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyFlag {
+    Prod,
+    Dev,
+    Optional,
+    Peer,
+    Exact,
+    Tilde,
+    Bundle,
+    NoSave,
+}
+
+// Spellings of each dependency-type flag per package manager. The first spelling is the
+// one we render; an empty list means the behavior is the default and needs no flag.
+// Package managers missing for a flag have no equivalent for it.
+const DEPENDENCY_FLAGS: &[(DependencyFlag, PackageManager, &[&str])] = &[
+    (
+        DependencyFlag::Prod,
+        Npm,
+        &["-P", "--save-prod", "-S", "--save"],
+    ),
+    (DependencyFlag::Prod, Pnpm, &["-P", "--save-prod"]),
+    (DependencyFlag::Prod, Yarn, &[]),
+    (DependencyFlag::Prod, Bun, &[]),
+    (DependencyFlag::Prod, Deno, &[]),
+    (DependencyFlag::Dev, Npm, &["-D", "--save-dev"]),
+    (DependencyFlag::Dev, Pnpm, &["-D", "--save-dev"]),
+    (DependencyFlag::Dev, Yarn, &["-D", "--dev"]),
+    (
+        DependencyFlag::Dev,
+        Bun,
+        &["-D", "-d", "--dev", "--development"],
+    ),
+    (DependencyFlag::Dev, Deno, &["-D", "--dev"]),
+    (DependencyFlag::Optional, Npm, &["-O", "--save-optional"]),
+    (DependencyFlag::Optional, Pnpm, &["-O", "--save-optional"]),
+    (DependencyFlag::Optional, Yarn, &["-O", "--optional"]),
+    (DependencyFlag::Optional, Bun, &["--optional"]),
+    (DependencyFlag::Peer, Npm, &["--save-peer"]),
+    (DependencyFlag::Peer, Pnpm, &["--save-peer"]),
+    (DependencyFlag::Peer, Yarn, &["-P", "--peer"]),
+    (DependencyFlag::Peer, Bun, &["--peer"]),
+    (DependencyFlag::Exact, Npm, &["-E", "--save-exact"]),
+    (DependencyFlag::Exact, Pnpm, &["-E", "--save-exact"]),
+    (DependencyFlag::Exact, Yarn, &["-E", "--exact"]),
+    (DependencyFlag::Exact, Bun, &["-E", "--exact"]),
+    (DependencyFlag::Tilde, Yarn, &["-T", "--tilde"]),
+    (DependencyFlag::Bundle, Npm, &["-B", "--save-bundle"]),
+    (DependencyFlag::NoSave, Npm, &["--no-save"]),
+    (DependencyFlag::NoSave, Bun, &["--no-save"]),
+];
+
+pub fn parse_dependency_flag(pm: PackageManager, flag: &str) -> Option<DependencyFlag> {
+    DEPENDENCY_FLAGS
+        .iter()
+        .find(|(_, flag_pm, spellings)| *flag_pm == pm && spellings.contains(&flag))
+        .map(|(dependency_flag, _, _)| *dependency_flag)
+}
+
+pub fn dependency_flag_spellings(
+    flag: DependencyFlag,
+    pm: PackageManager,
+) -> Option<&'static [&'static str]> {
+    DEPENDENCY_FLAGS
+        .iter()
+        .find(|(dependency_flag, flag_pm, _)| *dependency_flag == flag && *flag_pm == pm)
+        .map(|(_, _, spellings)| *spellings)
+}
+
+// Runner options, besides `--yes`, `--package` and the shell command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunnerFlag {
+    // Print less about what the runner does
+    Quiet,
+    // Print more about what the runner does
+    Verbose,
+    // Fail rather than install a package that isn't installed yet
+    NoInstall,
+    // Run the package with bun instead of node
+    Bun,
+}
+
+// Spellings of each runner option per package manager's runner, the first one rendered.
+// Runners missing for an option have no equivalent for it.
+const RUNNER_FLAGS: &[(RunnerFlag, PackageManager, &[&str])] = &[
+    (RunnerFlag::Quiet, Npm, &["--silent", "-s", "--quiet", "-q"]),
+    (RunnerFlag::Quiet, Pnpm, &["--silent", "-s"]),
+    (RunnerFlag::Quiet, Yarn, &["--quiet", "-q"]),
+    (RunnerFlag::Quiet, Bun, &["--silent"]),
+    (RunnerFlag::Quiet, Deno, &["--quiet", "-q"]),
+    (RunnerFlag::Verbose, Npm, &["--verbose"]),
+    (RunnerFlag::Verbose, Bun, &["--verbose"]),
+    (RunnerFlag::NoInstall, Npm, &["--no", "--no-install"]),
+    (RunnerFlag::NoInstall, Bun, &["--no-install"]),
+    (RunnerFlag::Bun, Bun, &["--bun"]),
+];
+
+pub fn parse_runner_flag(pm: PackageManager, flag: &str) -> Option<RunnerFlag> {
+    RUNNER_FLAGS
+        .iter()
+        .find(|(_, flag_pm, spellings)| *flag_pm == pm && spellings.contains(&flag))
+        .map(|(runner_flag, _, _)| *runner_flag)
+}
+
+pub fn runner_flag_spellings(
+    flag: RunnerFlag,
+    pm: PackageManager,
+) -> Option<&'static [&'static str]> {
+    RUNNER_FLAGS
+        .iter()
+        .find(|(runner_flag, flag_pm, _)| *runner_flag == flag && *flag_pm == pm)
+        .map(|(_, _, spellings)| *spellings)
+}
+
+// Splits `name@version` into name and version, minding the `@` of scoped packages
+fn split_version(spec: &str) -> (&str, Option<&str>) {
+    let Some(first) = spec.chars().next() else {
+        return (spec, None);
+    };
+    let start = first.len_utf8();
+    match spec[start..].find('@') {
+        Some(at) => (&spec[..start + at], Some(&spec[start + at + 1..])),
+        None => (spec, None),
+    }
+}
+
+// `vite` -> `create-vite`, `@scope` -> `@scope/create`, `@scope/app` -> `@scope/create-app`
+pub fn initializer_to_package(initializer: &str) -> Option<String> {
+    if initializer.is_empty() || initializer.starts_with('.') {
+        return None;
+    }
+
+    let (name, version) = split_version(initializer);
+    let package = match name.strip_prefix('@') {
+        Some(scoped) => match scoped.split_once('/') {
+            Some((scope, app)) => format!("@{}/create-{}", scope, app),
+            None => format!("@{}/create", scoped),
+        },
+        // Anything else with a slash is a repository or a path, not an npm initializer
+        None if name.contains('/') || name.contains(':') => return None,
+        None => format!("create-{}", name),
+    };
+
+    Some(match version {
+        Some(version) => format!("{}@{}", package, version),
+        None => package,
+    })
+}
+
+// The reverse of `initializer_to_package`, for runner commands like `npx create-vite`
+pub fn initializer_from_package(package: &str) -> Option<String> {
+    let (name, version) = split_version(package);
+    let initializer = match name.strip_prefix('@') {
+        Some(scoped) => {
+            let (scope, app) = scoped.split_once('/')?;
+            match app {
+                "create" => format!("@{}", scope),
+                _ => format!("@{}/{}", scope, app.strip_prefix("create-")?),
+            }
+        }
+        None => name.strip_prefix("create-")?.to_string(),
+    };
+
+    Some(match version {
+        Some(version) => format!("{}@{}", initializer, version),
+        None => initializer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initializer_packages() {
+        assert_eq!(
+            initializer_to_package("vite@latest"),
+            Some("create-vite@latest".to_string())
+        );
+        assert_eq!(
+            initializer_to_package("@scope"),
+            Some("@scope/create".to_string())
+        );
+        assert_eq!(
+            initializer_to_package("@scope/app@1.0.0"),
+            Some("@scope/create-app@1.0.0".to_string())
+        );
+        assert_eq!(initializer_to_package("user/repo"), None);
+        assert_eq!(initializer_to_package("./template"), None);
+        assert_eq!(
+            initializer_from_package("@scope/create"),
+            Some("@scope".to_string())
+        );
+        assert_eq!(
+            initializer_from_package("create-next-app@14"),
+            Some("next-app@14".to_string())
+        );
+        assert_eq!(initializer_from_package("cowsay"), None);
+    }
+
+    #[test]
+    fn test_split_version() {
+        assert_eq!(split_version("react@18"), ("react", Some("18")));
+        assert_eq!(
+            split_version("@scope/app@1.0.0"),
+            ("@scope/app", Some("1.0.0"))
+        );
+        assert_eq!(split_version("@scope/app"), ("@scope/app", None));
+        assert_eq!(split_version("é@1"), ("é", Some("1")));
+        assert_eq!(split_version("é"), ("é", None));
+        assert_eq!(split_version(""), ("", None));
+    }
+
+    #[test]
+    fn test_dependency_flags() {
+        // Yarn's `-P` is a peer dependency, npm's a production one
+        assert_eq!(
+            parse_dependency_flag(Yarn, "-P"),
+            Some(DependencyFlag::Peer)
+        );
+        assert_eq!(parse_dependency_flag(Npm, "-P"), Some(DependencyFlag::Prod));
+        assert_eq!(parse_dependency_flag(Pnpm, "--dev"), None);
+        assert_eq!(
+            dependency_flag_spellings(DependencyFlag::Dev, Bun),
+            Some(&["-D", "-d", "--dev", "--development"][..])
+        );
+        assert_eq!(
+            dependency_flag_spellings(DependencyFlag::Prod, Yarn),
+            Some(&[][..])
+        );
+        assert_eq!(dependency_flag_spellings(DependencyFlag::Tilde, Npm), None);
+    }
+
+    #[test]
+    fn test_registry_packages() {
+        let is_registry_package = |value: &str| {
+            PackageSpec::new(Registry::Npm, Token::new(value)).source == SpecSource::Registry
+        };
+        assert!(is_registry_package("react@^18"));
+        assert!(is_registry_package("@types/node"));
+        assert!(!is_registry_package("./lib"));
+        assert!(!is_registry_package("user/repo"));
+        assert!(!is_registry_package("github:user/repo"));
+    }
+
+    #[test]
+    fn test_spec_sources() {
+        use SpecSource::*;
+
+        let alias = |name: &str, target: &str| Alias {
+            name: name.to_string(),
+            target: target.to_string(),
+        };
+        assert_eq!(
+            parse_spec_source("my-react@npm:react@18"),
+            alias("my-react", "react@18")
+        );
+        assert_eq!(
+            parse_spec_source("@my/react@npm:@scope/react"),
+            alias("@my/react", "@scope/react")
+        );
+
+        assert_eq!(parse_spec_source("github:user/repo#branch"), Git);
+        assert_eq!(parse_spec_source("user/repo#v1.0.0"), Git);
+        assert_eq!(
+            parse_spec_source("git+ssh://git@github.com/user/repo.git"),
+            Git
+        );
+        assert_eq!(
+            parse_spec_source("https://github.com/user/repo.git#main"),
+            Git
+        );
+        assert_eq!(parse_spec_source("repo@gitlab:user/repo"), Git);
+
+        assert_eq!(parse_spec_source("./pkg.tgz"), Tarball);
+        assert_eq!(parse_spec_source("https://example.com/pkg"), Tarball);
+        assert_eq!(parse_spec_source("file:../pkg-1.0.0.tar.gz"), Tarball);
+
+        assert_eq!(parse_spec_source("file:../lib"), Path);
+        assert_eq!(parse_spec_source("../lib"), Path);
+        assert_eq!(parse_spec_source("~/lib"), Path);
+        assert_eq!(parse_spec_source("lib@file:./lib"), Path);
+
+        assert_eq!(
+            parse_spec_source("link:../lib"),
+            Link {
+                target: "../lib".to_string()
+            }
+        );
+        assert_eq!(
+            parse_spec_source("my-lib@workspace:*"),
+            Workspace {
+                name: "my-lib".to_string()
+            }
+        );
+
+        assert_eq!(parse_spec_source("@types/node@^20"), Registry);
+        assert_eq!(parse_spec_source("react@latest"), Registry);
+    }
+}
@@ -0,0 +1,47 @@
+//! Translates package manager commands between npm, pnpm, Yarn, Bun and Deno.
+//!
+//! This is the engine of the PackSwitcher menubar app, usable without it:
+//!
+//! - [`translate`] rewrites the commands in a copied text, from a single command to a
+//!   Markdown code block or prose with inline code, for another package manager.
+//! - [`translate_document`] does the same for a Markdown or text document, only touching
+//!   commands in code blocks and inline code, and [`find_documents`] finds the documents
+//!   in a directory.
+//! - [`detect_package_manager`] tells which package manager a command is written for.
+//! - [`load_rules`] and [`parse_rules`] read user rules, equivalent spellings of a
+//!   command that win over the built-in translation.
+//!
+//! ```
+//! use packswitcher_core::{parse_rules, translate, PackageManager, RulesFormat, YarnDialect};
+//!
+//! let rules = parse_rules(
+//!     "[[rules]]\nnpm = \"run lint:fix\"\npnpm = \"lint --fix\"",
+//!     RulesFormat::Toml,
+//! )
+//! .unwrap();
+//!
+//! let result = translate(
+//!     "```sh\nnpm ci\nnpm run lint:fix\n```",
+//!     PackageManager::Pnpm,
+//!     YarnDialect::Berry,
+//!     &rules,
+//! )
+//! .unwrap();
+//! assert_eq!(result.translated, "```sh\npnpm install --frozen-lockfile\npnpm lint --fix\n```");
+//! assert_eq!(result.rules, vec!["frozen install", "rule 1"]);
+//! ```
+
+mod documents;
+mod ir;
+mod package_manager;
+mod parse;
+mod render;
+mod rules;
+mod shell;
+mod snippet;
+mod translate;
+
+pub use documents::find_documents;
+pub use package_manager::{PackageManager, UnknownPackageManager, YarnDialect};
+pub use rules::{load_rules, parse_rules, Rule, RulesFormat};
+pub use translate::{detect_package_manager, translate, translate_document, TranslationResult};
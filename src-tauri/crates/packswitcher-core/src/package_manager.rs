@@ -0,0 +1,134 @@
+// The package managers commands are translated between.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A package manager, named after its executable (`npm`, `pnpm`, `yarn`, `bun`, `deno`) when
+/// parsed, displayed or serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+    Deno,
+}
+
+impl PackageManager {
+    /// Every package manager, in the order they are offered to the user
+    pub const ALL: [PackageManager; 5] = [
+        PackageManager::Npm,
+        PackageManager::Pnpm,
+        PackageManager::Yarn,
+        PackageManager::Bun,
+        PackageManager::Deno,
+    ];
+
+    /// The name of the package manager's executable
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Yarn => "yarn",
+            PackageManager::Bun => "bun",
+            PackageManager::Deno => "deno",
+        }
+    }
+}
+
+impl fmt::Display for PackageManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PackageManager {
+    type Err = UnknownPackageManager;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        PackageManager::ALL
+            .into_iter()
+            .find(|pm| pm.as_str() == value)
+            .ok_or_else(|| UnknownPackageManager {
+                value: value.to_string(),
+                expected: PackageManager::ALL.to_vec(),
+            })
+    }
+}
+
+/// The error for a name that isn't a package manager. Serializes as
+/// `{ "value": "pnmp", "expected": ["npm", "pnpm", ...] }`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct UnknownPackageManager {
+    pub value: String,
+    pub expected: Vec<PackageManager>,
+}
+
+impl fmt::Display for UnknownPackageManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected: Vec<&str> = self.expected.iter().map(|pm| pm.as_str()).collect();
+        write!(
+            f,
+            "Unknown package manager `{}`, expected one of {}",
+            self.value,
+            expected.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownPackageManager {}
+
+/// The Yarn version to translate for. Yarn 1 (Classic) and Yarn 2+ (Berry) disagree on
+/// several commands (`dlx`, `global`, `upgrade`, ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum YarnDialect {
+    Classic,
+    Berry,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        for pm in PackageManager::ALL {
+            assert_eq!(pm.to_string().parse::<PackageManager>(), Ok(pm));
+        }
+        assert_eq!("pnpm".parse::<PackageManager>(), Ok(PackageManager::Pnpm));
+        assert_eq!(PackageManager::Deno.to_string(), "deno");
+    }
+
+    #[test]
+    fn test_unknown_package_manager() {
+        let error = "pnmp".parse::<PackageManager>().unwrap_err();
+        assert_eq!(error.value, "pnmp");
+        assert_eq!(
+            error.to_string(),
+            "Unknown package manager `pnmp`, expected one of npm, pnpm, yarn, bun, deno"
+        );
+        assert!("NPM".parse::<PackageManager>().is_err());
+        assert!("".parse::<PackageManager>().is_err());
+    }
+
+    #[test]
+    fn test_serde() {
+        assert_eq!(
+            serde_json::to_string(&PackageManager::Yarn).unwrap(),
+            "\"yarn\""
+        );
+        assert_eq!(
+            serde_json::from_str::<PackageManager>("\"bun\"").unwrap(),
+            PackageManager::Bun
+        );
+        assert_eq!(
+            serde_json::to_value("x".parse::<PackageManager>().unwrap_err()).unwrap(),
+            serde_json::json!({
+                "value": "x",
+                "expected": ["npm", "pnpm", "yarn", "bun", "deno"]
+            })
+        );
+    }
+}
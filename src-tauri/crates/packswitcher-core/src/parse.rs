@@ -0,0 +1,1247 @@
+// Parses package manager commands into the intermediate representation in `ir`.
+
+use crate::ir::{
+    initializer_from_package, initializer_to_package, parse_dependency_flag, parse_runner_flag,
+    Arg, Command, Invocation, Operation, PackageSpec, Registry, WorkspaceScope,
+};
+use crate::package_manager::PackageManager::{self, Bun, Deno, Npm, Pnpm, Yarn};
+use crate::shell::{self, Token};
+
+// Parses a simple command (no `&&`, pipes, ...) written for any supported package manager
+// or package runner. Anything else, including commands that don't parse as shell words,
+// gives `None`.
+pub fn parse(command: &str) -> Option<Command> {
+    // Redirections aren't arguments, wherever they are written
+    let (redirections, tokens): (Vec<Token>, Vec<Token>) = shell::tokenize(command)
+        .ok()?
+        .into_iter()
+        .partition(Token::is_redirection);
+    let (program, args) = tokens.split_first()?;
+
+    let command = match program.value.as_str() {
+        "npx" => parse_runner(Npm, args),
+        "pnpx" => parse_runner(Pnpm, args),
+        "bunx" => parse_runner(Bun, args),
+        "deno" => parse_deno(args),
+        name => parse_package_manager(name.parse().ok()?, args),
+    }?;
+    Some(Command {
+        redirections,
+        ..command
+    })
+}
+
+fn parse_package_manager(pm: PackageManager, args: &[Token]) -> Option<Command> {
+    let command = |scope, operation| {
+        Some(Command {
+            pm,
+            scope,
+            operation,
+            redirections: Vec::new(),
+        })
+    };
+
+    // A bare `yarn` installs from the lockfile
+    if pm == Yarn && args.is_empty() {
+        return command(
+            None,
+            Operation::Install {
+                frozen: false,
+                args: Vec::new(),
+            },
+        );
+    }
+
+    let subcommand = args.first()?.value.as_str();
+    match (pm, subcommand) {
+        (Pnpm | Yarn, "dlx") | (Bun, "x") => return parse_runner(pm, &args[1..]),
+        (Npm, "create" | "init" | "innit") | (Pnpm | Yarn | Bun, "create") => {
+            // Otherwise it's `npm init` creating a package.json, or a bun-only template
+            if let Some(create) = parse_create(pm, &args[1..]) {
+                return command(None, create);
+            }
+        }
+        _ => {}
+    }
+
+    // Workspace options can come before the subcommand (`pnpm --filter web add zod`)
+    let (scope, tokens) = take_workspace_scope(pm, args);
+    // Other options of the package manager can too (`pnpm --silent build`)
+    let (options, tokens) = split_leading_options(&tokens);
+    let (subcommand, args) = tokens.split_first()?;
+
+    // Yarn prefixes some commands: `yarn global add <pkg>`, `yarn npm publish`
+    let (subcommand, args, global) = match (pm, subcommand.value.as_str(), args.first()) {
+        (Yarn, "global", Some(next)) if ["add", "remove"].contains(&next.value.as_str()) => {
+            (next, &args[1..], true)
+        }
+        (Yarn, "npm", Some(next)) if next.value == "publish" => (next, &args[1..], false),
+        _ => (subcommand, args, false),
+    };
+
+    let is_frozen_flag = |token: &Token| match pm {
+        Yarn => ["--frozen-lockfile", "--immutable"].contains(&token.value.as_str()),
+        _ => token.value == "--frozen-lockfile",
+    };
+    let frozen_install = |args: &[Token]| Operation::Install {
+        frozen: true,
+        args: args
+            .iter()
+            .filter(|token| !is_frozen_flag(token))
+            .cloned()
+            .collect(),
+    };
+
+    let operation = match (pm, canonical_subcommand(pm, &subcommand.value)) {
+        (Npm, "ci" | "clean-install" | "ic" | "install-clean" | "isntall-clean") => {
+            frozen_install(args)
+        }
+        (Pnpm | Yarn | Bun, "install") if args.iter().any(is_frozen_flag) => frozen_install(args),
+        // A bare `yarn --frozen-lockfile` installs too
+        (Yarn, _) if is_frozen_flag(subcommand) => frozen_install(tokens),
+        (_, name @ ("install" | "add")) => {
+            let (is_global, args) = take_global_flag(&expand_short_flags(args, pm));
+            let global = global || is_global;
+            if name != "add" && !global && !names_packages(&args) {
+                Operation::Install {
+                    frozen: false,
+                    args,
+                }
+            } else {
+                Operation::Add {
+                    args: parse_package_args(pm, &args),
+                    global,
+                }
+            }
+        }
+        (_, "uninstall" | "remove") => {
+            let (is_global, args) = take_global_flag(&expand_short_flags(args, pm));
+            Operation::Remove {
+                args: parse_package_args(pm, &args),
+                global: global || is_global,
+            }
+        }
+        (_, "run") => {
+            // The package manager's own options come before the script name
+            match args.iter().position(|arg| !arg.value.starts_with('-')) {
+                Some(script) => {
+                    let (mut options, script_args) = script_args(pm, &args[script + 1..]);
+                    options.splice(0..0, args[..script].iter().cloned());
+                    Operation::Run {
+                        invocation: Invocation::Run,
+                        options,
+                        script: Some(args[script].clone()),
+                        args: script_args,
+                    }
+                }
+                None => Operation::Run {
+                    invocation: Invocation::Run,
+                    options: args.to_vec(),
+                    script: None,
+                    args: Vec::new(),
+                },
+            }
+        }
+        // `bun test` runs bun's own test runner, which no other package manager has
+        (Bun, "test") => return None,
+        (Npm | Pnpm | Bun, shortcut) if NPM_SCRIPT_SHORTCUTS.contains(&shortcut) => {
+            let (options, args) = script_args(pm, args);
+            Operation::Run {
+                invocation: Invocation::Shortcut,
+                options,
+                script: Some(Token::new(shortcut)),
+                args,
+            }
+        }
+        (Yarn, script) if !is_builtin(Yarn, script) => bare_run(subcommand, args),
+        (Yarn, command) if YARN_ONLY.contains(&command) => return None,
+        (Npm | Pnpm | Bun, "update") | (Yarn, "up" | "upgrade") => Operation::Update {
+            args: args.to_vec(),
+        },
+        // `bun upgrade` upgrades bun itself, which no other package manager can
+        (Bun, "upgrade") => return None,
+        (_, "publish") => Operation::Publish {
+            args: args.to_vec(),
+        },
+        (Npm | Pnpm | Yarn, "exec") => Operation::Exec {
+            args: match args.split_first() {
+                Some((separator, rest)) if separator.value == "--" => rest.to_vec(),
+                _ => args.to_vec(),
+            },
+        },
+        // Like Yarn, pnpm and bun run any script by name
+        (Pnpm | Bun, script) if !is_builtin(pm, script) => bare_run(subcommand, args),
+        _ => Operation::Other {
+            subcommand: subcommand.clone(),
+            args: args.to_vec(),
+        },
+    };
+
+    command(scope, with_leading_options(operation, options)?)
+}
+
+// Splits off the options in front of the subcommand. Without a subcommand after them,
+// there are none.
+fn split_leading_options(tokens: &[Token]) -> (&[Token], &[Token]) {
+    let mut i = 0;
+    while let Some(token) = tokens.get(i) {
+        if token.value == "--" || !token.value.starts_with('-') {
+            break;
+        }
+        i += match LEADING_VALUE_OPTIONS.contains(&token.value.as_str()) {
+            true => 2,
+            false => 1,
+        };
+    }
+    match tokens.get(i) {
+        Some(token) if token.value != "--" => tokens.split_at(i),
+        _ => (&[], tokens),
+    }
+}
+
+// Puts the options from in front of the subcommand back, ahead of a script name or the
+// other arguments. `exec` would pass them on to the binary, so it can't take any.
+fn with_leading_options(mut operation: Operation, leading: &[Token]) -> Option<Operation> {
+    if leading.is_empty() {
+        return Some(operation);
+    }
+    match &mut operation {
+        Operation::Run { options, .. } => {
+            options.splice(0..0, leading.iter().cloned());
+        }
+        Operation::Install { args, .. }
+        | Operation::Update { args }
+        | Operation::Publish { args }
+        | Operation::Other { args, .. } => {
+            args.splice(0..0, leading.iter().cloned());
+        }
+        Operation::Add { args, .. } | Operation::Remove { args, .. } => {
+            args.splice(0..0, leading.iter().cloned().map(Arg::Other));
+        }
+        Operation::Exec { .. } | Operation::Dlx { .. } | Operation::Create { .. } => return None,
+    }
+    Some(operation)
+}
+
+// The subcommand an alias or common typo stands for, like npm's `i` for `install`
+fn canonical_subcommand(pm: PackageManager, subcommand: &str) -> &str {
+    match (pm, subcommand) {
+        (
+            Npm,
+            "i" | "in" | "ins" | "inst" | "insta" | "instal" | "isnt" | "isnta" | "isntal"
+            | "isntall" | "add",
+        ) => "install",
+        (Npm, "un" | "unlink" | "remove" | "rm" | "r") => "uninstall",
+        (Npm, "up" | "upgrade" | "udpate") => "update",
+        (Npm, "x") => "exec",
+        (Npm, "run-script" | "rum" | "urn") => "run",
+        (Npm, "t" | "tst") => "test",
+        (Pnpm | Yarn | Bun, "i") => "install",
+        (Pnpm, "rm" | "un" | "uninstall") | (Bun, "rm") => "remove",
+        (Pnpm, "up" | "upgrade") => "update",
+        (Pnpm, "run-script") => "run",
+        (Bun, "a") => "add",
+        _ => subcommand,
+    }
+}
+
+// A script run by name, as in `yarn build`
+fn bare_run(script: &Token, args: &[Token]) -> Operation {
+    Operation::Run {
+        invocation: Invocation::Bare,
+        options: Vec::new(),
+        script: Some(script.clone()),
+        args: without_separator(args),
+    }
+}
+
+// Removes the first `--`: npm needs it in front of script arguments, the others pass it
+// on to the script
+fn without_separator(args: &[Token]) -> Vec<Token> {
+    let mut args = args.to_vec();
+    if let Some(separator) = args.iter().position(|arg| arg.value == "--") {
+        args.remove(separator);
+    }
+    args
+}
+
+// Splits the arguments after a script name into the package manager's options and the
+// script's arguments. npm reads options up to `--` as its own settings
+// (`npm run build --if-present`) and only passes on words and what follows `--`, while the
+// others pass on everything.
+fn script_args(pm: PackageManager, args: &[Token]) -> (Vec<Token>, Vec<Token>) {
+    if pm != Npm {
+        return (Vec::new(), without_separator(args));
+    }
+
+    let mut options = Vec::new();
+    let mut script_args = Vec::new();
+    let mut tokens = args.iter();
+    while let Some(token) = tokens.next() {
+        if token.value == "--" {
+            script_args.extend(tokens.cloned());
+            break;
+        }
+        if token.value.starts_with('-') {
+            options.push(token.clone());
+            if LEADING_VALUE_OPTIONS.contains(&token.value.as_str()) {
+                options.extend(tokens.next().cloned());
+            }
+        } else {
+            script_args.push(token.clone());
+        }
+    }
+    (options, script_args)
+}
+
+// Runner arguments: `npx create-vite`, `npx jsr add @std/path` or `npx cowsay hello`
+fn parse_runner(pm: PackageManager, args: &[Token]) -> Option<Command> {
+    if args.is_empty() {
+        return None;
+    }
+
+    // Runner options come before the package to run
+    let mut yes = false;
+    let mut flags = Vec::new();
+    let mut packages = Vec::new();
+    let mut shell_command = None;
+    let mut shell_mode = false;
+    let mut rest = args;
+    while let Some((option, tail)) = rest.split_first() {
+        let (name, inline_value) = match option.value.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(Token::new(value))),
+            _ => (option.value.as_str(), None),
+        };
+        let takes_value = match (pm, name) {
+            (_, "-y" | "--yes") => {
+                yes = true;
+                false
+            }
+            (_, "-p" | "--package") | (Npm, "-c" | "--call") => true,
+            // pnpm's `-c` runs the command after it in a shell
+            (Pnpm, "-c" | "--shell-mode") => {
+                shell_mode = true;
+                false
+            }
+            _ => match parse_runner_flag(pm, &option.value) {
+                Some(flag) => {
+                    flags.push((flag, option.clone()));
+                    false
+                }
+                None => break,
+            },
+        };
+        rest = tail;
+
+        if takes_value {
+            let value = match inline_value {
+                Some(value) => value,
+                None => {
+                    let (value, tail) = rest.split_first()?;
+                    rest = tail;
+                    value.clone()
+                }
+            };
+            match name {
+                "-c" | "--call" => shell_command = Some(value),
+                _ => packages.push(value),
+            }
+        }
+    }
+    if shell_mode {
+        let (command, tail) = rest.split_first()?;
+        shell_command = Some(command.clone());
+        rest = tail;
+    }
+
+    // Options make it more than a plain `npx jsr add` or `npx create-vite`
+    let is_plain = flags.is_empty() && packages.is_empty() && shell_command.is_none();
+    let values: Vec<&str> = rest.iter().map(|arg| arg.value.as_str()).collect();
+    let initializer = match values.first() {
+        Some(package) if is_plain => initializer_from_package(package),
+        _ => None,
+    };
+    let operation = match (values.as_slice(), initializer) {
+        // `npx jsr add` is how the npm ecosystem installs JSR packages
+        (["jsr", "add", ..], _) if is_plain => Operation::Add {
+            args: parse_package_args(Npm, &rest[2..])
+                .into_iter()
+                .filter_map(|arg| match arg {
+                    Arg::Package(spec) => Some(Arg::Package(PackageSpec {
+                        registry: Registry::Jsr,
+                        ..spec
+                    })),
+                    // The jsr CLI's own options (`--npm`, `--pnpm`, ...)
+                    Arg::Other(_) => None,
+                    dependency => Some(dependency),
+                })
+                .collect(),
+            global: false,
+        },
+        (_, Some(initializer)) => Operation::Create {
+            initializer,
+            args: rest[1..].to_vec(),
+        },
+        _ => Operation::Dlx {
+            yes,
+            flags,
+            packages,
+            shell_command,
+            args: rest.to_vec(),
+        },
+    };
+
+    Some(Command {
+        pm,
+        scope: None,
+        operation,
+        redirections: Vec::new(),
+    })
+}
+
+// `npm create vite@latest my-app -- --template react`, `pnpm create vite`, ...
+fn parse_create(pm: PackageManager, args: &[Token]) -> Option<Operation> {
+    // A bare `npm init` (or `npm init -y`) creates a package.json instead
+    let (initializer, args) = args.split_first()?;
+    if initializer.value.starts_with('-') {
+        return None;
+    }
+
+    // bun can also create from GitHub repositories and local templates, which only bun knows
+    if pm == Bun && initializer_to_package(&initializer.value).is_none() {
+        return None;
+    }
+
+    // npm needs `--` to forward options to the initializer, the others pass them directly
+    let args = match pm {
+        Npm => without_separator(args),
+        _ => args.to_vec(),
+    };
+
+    Some(Operation::Create {
+        initializer: initializer.value.clone(),
+        args,
+    })
+}
+
+// Sorts the arguments of `add` and `remove` into packages, dependency-type flags and
+// other options (together with their values)
+fn parse_package_args(pm: PackageManager, args: &[Token]) -> Vec<Arg> {
+    let mut parsed = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if arg.value == "--" {
+            parsed.push(Arg::Other(arg.clone()));
+            parsed.extend(args.cloned().map(Arg::Other));
+            break;
+        }
+        if let Some(flag) = parse_dependency_flag(pm, &arg.value) {
+            parsed.push(Arg::Dependency(flag, arg.clone()));
+        } else if INSTALL_VALUE_FLAGS.contains(&arg.value.as_str()) {
+            parsed.push(Arg::Other(arg.clone()));
+            parsed.extend(args.next().cloned().map(Arg::Other));
+        } else if arg.value.starts_with('-') {
+            parsed.push(Arg::Other(arg.clone()));
+        } else {
+            // Newer pnpm and yarn versions take `jsr:` specs too, so they are kept as written
+            parsed.push(Arg::Package(PackageSpec::new(Registry::Npm, arg.clone())));
+        }
+    }
+
+    parsed
+}
+
+// Deno names the registry of a package: `jsr:@std/path`, `npm:react`
+fn parse_package_spec(token: &Token) -> PackageSpec {
+    match (strip_prefix(token, "jsr:"), strip_prefix(token, "npm:")) {
+        (Some(token), _) => PackageSpec::new(Registry::Jsr, token),
+        (_, Some(token)) => PackageSpec::new(Registry::Npm, token),
+        _ => PackageSpec::new(Registry::Npm, token.clone()),
+    }
+}
+
+// Removes a prefix from a token, keeping its original quoting where possible
+fn strip_prefix(token: &Token, prefix: &str) -> Option<Token> {
+    let value = token.value.strip_prefix(prefix)?;
+    Some(match token.raw.strip_prefix(prefix) {
+        Some(raw) => Token {
+            raw: raw.to_string(),
+            value: value.to_string(),
+        },
+        None => Token::new(value),
+    })
+}
+
+fn parse_deno(args: &[Token]) -> Option<Command> {
+    let (subcommand, args) = args.split_first()?;
+    let command = |scope, operation| {
+        Some(Command {
+            pm: Deno,
+            scope,
+            operation,
+            redirections: Vec::new(),
+        })
+    };
+
+    match subcommand.value.as_str() {
+        // `deno run -A npm:cowsay hello` runs an npm package like npx does
+        "run" => {
+            // Permission flags and other options of `deno run` come before the package
+            let package = args.iter().position(|arg| !arg.value.starts_with('-'))?;
+            let package_token = strip_prefix(&args[package], "npm:")?;
+            let rest = &args[package + 1..];
+
+            match initializer_from_package(&package_token.value) {
+                Some(initializer) => command(
+                    None,
+                    Operation::Create {
+                        initializer,
+                        args: rest.to_vec(),
+                    },
+                ),
+                None => {
+                    // Permission flags go, the other runners have every permission
+                    let flags = args[..package]
+                        .iter()
+                        .filter_map(|arg| Some((parse_runner_flag(Deno, &arg.value)?, arg.clone())))
+                        .collect();
+                    let mut args = vec![package_token];
+                    args.extend_from_slice(rest);
+                    command(
+                        None,
+                        Operation::Dlx {
+                            yes: false,
+                            flags,
+                            packages: Vec::new(),
+                            shell_command: None,
+                            args,
+                        },
+                    )
+                }
+            }
+        }
+        "task" => {
+            let (scope, task) = parse_deno_task(args);
+            command(scope, task)
+        }
+        _ => command(None, parse_deno_dependencies(&subcommand.value, args)?),
+    }
+}
+
+// `deno task --filter web dev --port 3000`
+fn parse_deno_task(args: &[Token]) -> (Option<WorkspaceScope>, Operation) {
+    let mut scope = None;
+    let mut names = Vec::new();
+    let mut options = Vec::new();
+    let mut args = args.iter();
+    let mut task = None;
+
+    while let Some(arg) = args.next() {
+        match arg.value.as_str() {
+            "-r" | "--recursive" => scope = Some(WorkspaceScope::All),
+            "--filter" => names.extend(args.next().map(|name| name.value.clone())),
+            option if option.starts_with("--filter=") => {
+                names.push(option["--filter=".len()..].to_string())
+            }
+            option if DENO_VALUE_FLAGS.contains(&option) => {
+                options.push(arg.clone());
+                options.extend(args.next().cloned());
+            }
+            option if option.starts_with('-') => options.push(arg.clone()),
+            _ => {
+                task = Some(arg.clone());
+                break;
+            }
+        }
+    }
+
+    if !names.is_empty() {
+        scope = Some(WorkspaceScope::Named(names));
+    }
+
+    let operation = Operation::Run {
+        invocation: Invocation::Run,
+        options,
+        script: task,
+        args: args.cloned().collect(),
+    };
+    (scope, operation)
+}
+
+// `deno add`, `deno install`, `deno remove`, `deno uninstall` and `deno outdated --update`
+fn parse_deno_dependencies(subcommand: &str, tokens: &[Token]) -> Option<Operation> {
+    let mut flags = Vec::new();
+    let mut packages = Vec::new();
+    let mut args = tokens.iter();
+    while let Some(arg) = args.next() {
+        if arg.value.starts_with('-') {
+            if DENO_VALUE_FLAGS.contains(&arg.value.as_str()) {
+                args.next();
+            }
+            flags.push(arg);
+        } else {
+            packages.push(Arg::Package(parse_package_spec(arg)));
+        }
+    }
+
+    let has_flag = |names: &[&str]| {
+        flags
+            .iter()
+            .any(|flag| names.contains(&flag.value.as_str()))
+    };
+    let global = has_flag(&["-g", "--global"]);
+
+    // Permissions and the other options of Deno's installer mean nothing to the others,
+    // only the dependency type is kept
+    let mut package_args: Vec<Arg> = flags
+        .iter()
+        .filter_map(|flag| {
+            parse_dependency_flag(Deno, &flag.value)
+                .map(|dependency| Arg::Dependency(dependency, (*flag).clone()))
+        })
+        .collect();
+    package_args.extend(packages.iter().cloned());
+
+    let is_jsr = |arg: &Arg| matches!(arg, Arg::Package(spec) if spec.registry == Registry::Jsr);
+
+    match subcommand {
+        "add" | "install" | "i" if !packages.is_empty() => {
+            // Global JSR installs are Deno programs, there is nothing to translate them to
+            if global && packages.iter().any(is_jsr) {
+                return None;
+            }
+            Some(Operation::Add {
+                args: package_args,
+                global,
+            })
+        }
+        "install" | "i" => {
+            let is_frozen_flag = |token: &Token| token.value.starts_with("--frozen");
+            Some(Operation::Install {
+                frozen: tokens.iter().any(is_frozen_flag),
+                args: tokens
+                    .iter()
+                    .filter(|token| !is_frozen_flag(token))
+                    .cloned()
+                    .collect(),
+            })
+        }
+        "remove" | "rm" | "uninstall" => Some(Operation::Remove {
+            args: packages,
+            global,
+        }),
+        "outdated" if has_flag(&["-u", "--update"]) => Some(Operation::Update {
+            args: packages
+                .into_iter()
+                .filter_map(|arg| match arg {
+                    Arg::Package(spec) => Some(spec.token),
+                    _ => None,
+                })
+                .collect(),
+        }),
+        _ => None,
+    }
+}
+
+// Removes workspace options from a command (everything after the package manager name),
+// returning the scope they select and the remaining tokens
+fn take_workspace_scope(
+    pm: PackageManager,
+    tokens: &[Token],
+) -> (Option<WorkspaceScope>, Vec<Token>) {
+    let mut names = Vec::new();
+    let mut scope = None;
+    let mut remaining = Vec::new();
+
+    let mut tokens = tokens.iter().peekable();
+    let mut subcommand = None;
+
+    // Yarn selects workspaces with a command prefix
+    if pm == Yarn {
+        let mut lookahead = tokens.clone();
+        match (
+            lookahead.next().map(|token| token.value.as_str()),
+            lookahead.next(),
+        ) {
+            (Some("workspace"), Some(name)) if lookahead.peek().is_some() => {
+                names.push(name.value.clone());
+                tokens = lookahead;
+            }
+            (Some("workspaces"), Some(action)) if action.value == "foreach" => {
+                // `foreach` options come before the command; only `--include` narrows the scope
+                while let Some(option) = lookahead.next_if(|token| token.value.starts_with('-')) {
+                    match option.value.as_str() {
+                        "--include" => names.extend(lookahead.next().map(|t| t.value.clone())),
+                        "--exclude" | "--from" | "-j" | "--jobs" => {
+                            lookahead.next();
+                        }
+                        _ => {}
+                    }
+                }
+                scope = Some(WorkspaceScope::All);
+                tokens = lookahead;
+            }
+            // Yarn 1 runs a script in every workspace with `yarn workspaces run <script>`
+            (Some("workspaces"), Some(action)) if action.value == "run" => {
+                scope = Some(WorkspaceScope::All);
+                remaining.push(Token::new("run"));
+                subcommand = Some("run");
+                tokens = lookahead;
+            }
+            _ => {}
+        }
+    }
+
+    while let Some(token) = tokens.next() {
+        if token.value == "--" {
+            remaining.push(token.clone());
+            remaining.extend(tokens.cloned());
+            break;
+        }
+
+        // pnpm, yarn and bun pass everything after a script name or the binary of `exec` on
+        // to it, while npm reads its own options up to `--`
+        if pm != Npm && !token.value.starts_with('-') {
+            let passes_on_rest = match subcommand {
+                None => !is_builtin(pm, &token.value),
+                Some(subcommand) => ["run", "run-script", "exec"].contains(&subcommand),
+            };
+            subcommand = subcommand.or(Some(token.value.as_str()));
+            if passes_on_rest {
+                remaining.push(token.clone());
+                remaining.extend(tokens.cloned());
+                break;
+            }
+        }
+
+        let (option, inline_value) = match token.value.split_once('=') {
+            Some((option, value)) if option.starts_with('-') => (option, Some(value.to_string())),
+            _ => (token.value.as_str(), None),
+        };
+
+        let is_name_option = match pm {
+            Pnpm | Bun => ["--filter", "-F"].contains(&option),
+            Npm => ["--workspace", "-w"].contains(&option),
+            _ => false,
+        };
+        if is_name_option {
+            names.extend(inline_value.or_else(|| tokens.next().map(|t| t.value.clone())));
+            continue;
+        }
+
+        match (pm, option) {
+            (Pnpm, "-r" | "--recursive") | (Npm, "-ws" | "--workspaces") => {
+                scope = Some(WorkspaceScope::All)
+            }
+            (Pnpm, "-w" | "--workspace-root") | (Yarn, "-W" | "--ignore-workspace-root-check") => {
+                scope = Some(WorkspaceScope::Root)
+            }
+            _ => remaining.push(token.clone()),
+        }
+    }
+
+    // `--filter '*'` is bun's way of selecting every workspace
+    if names.len() == 1 && names[0] == "*" {
+        return (Some(WorkspaceScope::All), remaining);
+    }
+
+    if !names.is_empty() {
+        scope = Some(WorkspaceScope::Named(names));
+    }
+
+    (scope, remaining)
+}
+
+// Whether a subcommand is one of the package manager's own rather than a script name
+fn is_builtin(pm: PackageManager, subcommand: &str) -> bool {
+    match pm {
+        Pnpm => PNPM_BUILTINS.contains(&subcommand),
+        Yarn => YARN_BUILTINS.contains(&subcommand),
+        Bun => BUN_BUILTINS.contains(&subcommand),
+        Npm | Deno => true,
+    }
+}
+
+// Splits combined short flags like `-gD` into `-g -D`, as long as every letter is a
+// boolean flag we know about. Anything else (e.g. npm's `-ws`) is left alone.
+fn expand_short_flags(args: &[Token], pm: PackageManager) -> Vec<Token> {
+    let is_short_flag = |letter: char| {
+        letter == 'g' || parse_dependency_flag(pm, &format!("-{}", letter)).is_some()
+    };
+
+    let mut expanded = Vec::new();
+    for (i, arg) in args.iter().enumerate() {
+        if arg.value == "--" {
+            expanded.extend_from_slice(&args[i..]);
+            break;
+        }
+
+        let letters = arg
+            .value
+            .strip_prefix('-')
+            .filter(|letters| letters.len() > 1 && !letters.starts_with('-'));
+        match letters {
+            Some(letters) if letters.chars().all(is_short_flag) => expanded.extend(
+                letters
+                    .chars()
+                    .map(|letter| Token::new(&format!("-{}", letter))),
+            ),
+            _ => expanded.push(arg.clone()),
+        }
+    }
+    expanded
+}
+
+// Removes `-g` / `--global` from the arguments, returning whether it was present
+fn take_global_flag(args: &[Token]) -> (bool, Vec<Token>) {
+    let mut is_global = false;
+    let mut remaining = Vec::new();
+    let mut after_separator = false;
+
+    for arg in args {
+        if !after_separator && (arg.value == "-g" || arg.value == "--global") {
+            is_global = true;
+            continue;
+        }
+        after_separator |= arg.value == "--";
+        remaining.push(arg.clone());
+    }
+
+    (is_global, remaining)
+}
+
+// Whether an install names packages to add, as opposed to installing everything
+fn names_packages(args: &[Token]) -> bool {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg.value == "--" {
+            break;
+        }
+        if INSTALL_VALUE_FLAGS.contains(&arg.value.as_str()) {
+            args.next();
+            continue;
+        }
+        if !arg.value.starts_with('-') {
+            return true;
+        }
+    }
+    false
+}
+
+// npm subcommands that run the package.json script of the same name
+const NPM_SCRIPT_SHORTCUTS: &[&str] = &["test", "start", "stop", "restart"];
+
+// Yarn subcommands that are not package.json scripts
+const YARN_BUILTINS: &[&str] = &[
+    "add",
+    "remove",
+    "install",
+    "uninstall",
+    "run",
+    "up",
+    "upgrade",
+    "publish",
+    "global",
+    "npm",
+    "dlx",
+    "exec",
+    "init",
+    "create",
+    "info",
+    "why",
+    "link",
+    "unlink",
+    "pack",
+    "config",
+    "cache",
+    "audit",
+    "outdated",
+    "list",
+    "login",
+    "logout",
+    "version",
+    "workspace",
+    "workspaces",
+    "bin",
+    "access",
+    "autoclean",
+    "check",
+    "constraints",
+    "dedupe",
+    "explain",
+    "generate-lock-entry",
+    "help",
+    "import",
+    "licenses",
+    "node",
+    "owner",
+    "patch",
+    "patch-commit",
+    "plugin",
+    "policies",
+    "rebuild",
+    "sdks",
+    "search",
+    "set",
+    "stage",
+    "tag",
+    "team",
+    "unplug",
+    "upgrade-interactive",
+    "versions",
+];
+
+// Yarn subcommands no other package manager has an equivalent for
+const YARN_ONLY: &[&str] = &[
+    "autoclean",
+    "check",
+    "constraints",
+    "explain",
+    "generate-lock-entry",
+    "node",
+    "plugin",
+    "policies",
+    "sdks",
+    "set",
+    "stage",
+    "unplug",
+    "upgrade-interactive",
+];
+
+// pnpm subcommands that are not package.json scripts, including the npm commands pnpm
+// passes on to npm
+const PNPM_BUILTINS: &[&str] = &[
+    "add",
+    "install",
+    "i",
+    "install-test",
+    "it",
+    "update",
+    "up",
+    "upgrade",
+    "remove",
+    "rm",
+    "uninstall",
+    "un",
+    "link",
+    "ln",
+    "unlink",
+    "import",
+    "rebuild",
+    "rb",
+    "prune",
+    "fetch",
+    "dedupe",
+    "patch",
+    "patch-commit",
+    "patch-remove",
+    "approve-builds",
+    "ignored-builds",
+    "audit",
+    "list",
+    "ls",
+    "ll",
+    "la",
+    "outdated",
+    "why",
+    "licenses",
+    "run",
+    "exec",
+    "dlx",
+    "create",
+    "init",
+    "store",
+    "publish",
+    "pack",
+    "deploy",
+    "root",
+    "bin",
+    "config",
+    "c",
+    "get",
+    "set",
+    "env",
+    "setup",
+    "server",
+    "doctor",
+    "cat-file",
+    "cat-index",
+    "find-hash",
+    "self-update",
+    "recursive",
+    "multi",
+    "m",
+    "help",
+    "access",
+    "adduser",
+    "bugs",
+    "deprecate",
+    "dist-tag",
+    "docs",
+    "edit",
+    "info",
+    "login",
+    "logout",
+    "owner",
+    "ping",
+    "prefix",
+    "profile",
+    "pkg",
+    "repo",
+    "search",
+    "star",
+    "stars",
+    "team",
+    "token",
+    "unpublish",
+    "unstar",
+    "version",
+    "view",
+    "whoami",
+];
+
+// bun subcommands that are not package.json scripts
+const BUN_BUILTINS: &[&str] = &[
+    "run",
+    "test",
+    "x",
+    "repl",
+    "exec",
+    "install",
+    "i",
+    "add",
+    "a",
+    "remove",
+    "rm",
+    "update",
+    "outdated",
+    "audit",
+    "link",
+    "unlink",
+    "publish",
+    "patch",
+    "patch-commit",
+    "pm",
+    "info",
+    "why",
+    "init",
+    "create",
+    "c",
+    "upgrade",
+    "build",
+    "completions",
+    "help",
+];
+
+// Options in front of the subcommand that take a separate value
+const LEADING_VALUE_OPTIONS: &[&str] = &["-C", "--dir", "--cwd", "--prefix", "--loglevel"];
+
+// Options of install/add that take a separate value
+const INSTALL_VALUE_FLAGS: &[&str] = &[
+    "--registry",
+    "--cache",
+    "--prefix",
+    "--cwd",
+    "--tag",
+    "--loglevel",
+    "--omit",
+    "--include",
+    "--network-timeout",
+    "--filter",
+    "-F",
+    "--workspace",
+    "-w",
+];
+
+// Options of Deno's `add`, `install` and `task` that take a separate value
+const DENO_VALUE_FLAGS: &[&str] = &[
+    "-n",
+    "--name",
+    "--root",
+    "-c",
+    "--config",
+    "--cwd",
+    "--filter",
+    "--lock",
+    "-L",
+    "--log-level",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::DependencyFlag;
+
+    #[test]
+    fn test_parse_operations() {
+        let operation = |command: &str| parse(command).unwrap().operation;
+
+        assert_eq!(
+            parse("npm i -D react"),
+            Some(Command {
+                pm: Npm,
+                scope: None,
+                operation: Operation::Add {
+                    args: vec![
+                        Arg::Dependency(DependencyFlag::Dev, Token::new("-D")),
+                        Arg::Package(PackageSpec::new(Registry::Npm, Token::new("react"))),
+                    ],
+                    global: false,
+                },
+                redirections: Vec::new(),
+            })
+        );
+        assert_eq!(
+            parse("pnpm --filter web run dev --port 3000"),
+            Some(Command {
+                pm: Pnpm,
+                scope: Some(WorkspaceScope::Named(vec!["web".to_string()])),
+                operation: Operation::Run {
+                    invocation: Invocation::Run,
+                    options: Vec::new(),
+                    script: Some(Token::new("dev")),
+                    args: vec![Token::new("--port"), Token::new("3000")],
+                },
+                redirections: Vec::new(),
+            })
+        );
+        assert_eq!(
+            parse("deno add jsr:@std/path").map(|command| command.operation),
+            Some(Operation::Add {
+                args: vec![Arg::Package(PackageSpec::new(
+                    Registry::Jsr,
+                    Token::new("@std/path"),
+                ))],
+                global: false,
+            })
+        );
+        assert_eq!(
+            parse("yarn --frozen-lockfile").map(|command| command.operation),
+            Some(Operation::Install {
+                frozen: true,
+                args: Vec::new(),
+            })
+        );
+        assert_eq!(
+            parse("bunx -y create-vite my-app").map(|command| command.operation),
+            Some(Operation::Create {
+                initializer: "vite".to_string(),
+                args: vec![Token::new("my-app")],
+            })
+        );
+
+        // Options in front of a script name are the package manager's
+        for command in [
+            "pnpm --silent build",
+            "pnpm -s build",
+            "yarn --silent build",
+        ] {
+            assert_eq!(
+                operation(command),
+                Operation::Run {
+                    invocation: Invocation::Bare,
+                    options: vec![Token::new(command.split(' ').nth(1).unwrap())],
+                    script: Some(Token::new("build")),
+                    args: Vec::new(),
+                }
+            );
+        }
+        assert_eq!(
+            operation("pnpm --dir app dev --port 3000"),
+            Operation::Run {
+                invocation: Invocation::Bare,
+                options: vec![Token::new("--dir"), Token::new("app")],
+                script: Some(Token::new("dev")),
+                args: vec![Token::new("--port"), Token::new("3000")],
+            }
+        );
+        assert_eq!(
+            operation("npm --silent run build"),
+            parse("npm run --silent build").unwrap().operation
+        );
+        assert_eq!(parse("pnpm --silent exec tsc"), None);
+
+        // Aliases stand for the subcommand
+        let aliases = [
+            ("npm un react", "pnpm remove react"),
+            ("npm r react", "pnpm remove react"),
+            ("npm unlink react", "pnpm remove react"),
+            ("npm isntall react", "pnpm add react"),
+            ("npm add react", "pnpm add react"),
+            ("npm x cowsay", "pnpm exec cowsay"),
+            ("npm t", "pnpm test"),
+            ("npm rum build", "pnpm run build"),
+            ("pnpm rm react", "pnpm remove react"),
+            ("pnpm un react", "pnpm remove react"),
+            ("pnpm up", "pnpm update"),
+            ("bun rm react", "pnpm remove react"),
+            ("bun a react", "pnpm add react"),
+            ("bun i", "pnpm install"),
+        ];
+        for (alias, subcommand) in aliases {
+            assert_eq!(
+                operation(alias),
+                parse(subcommand).unwrap().operation,
+                "{}",
+                alias
+            );
+        }
+
+        // pnpm, yarn and bun builtins are not scripts
+        for command in [
+            "pnpm store prune",
+            "pnpm why react",
+            "bun build ./index.ts",
+            "bun pm ls",
+            "yarn dedupe",
+            "yarn rebuild",
+            "yarn import",
+            "yarn licenses list",
+        ] {
+            assert!(matches!(operation(command), Operation::Other { .. }));
+        }
+        assert!(matches!(
+            operation("bun dev"),
+            Operation::Run {
+                invocation: Invocation::Bare,
+                ..
+            }
+        ));
+
+        // Not package manager commands
+        assert_eq!(parse("npm"), None);
+        assert_eq!(parse("bun upgrade"), None);
+        for command in [
+            "yarn set version stable",
+            "yarn node script.js",
+            "yarn plugin import x",
+            "yarn unplug x",
+        ] {
+            assert_eq!(parse(command), None);
+        }
+        assert_eq!(parse("cargo build"), None);
+        assert_eq!(parse("npm install 'react"), None);
+        assert_eq!(parse("deno run -A main.ts"), None);
+    }
+
+    #[test]
+    fn test_not_create_or_frozen() {
+        let operation = |command: &str| parse(command).unwrap().operation;
+
+        // `npm init -y` creates a package.json, `bun create` also takes GitHub repositories
+        assert!(matches!(operation("npm init -y"), Operation::Other { .. }));
+        assert!(matches!(
+            operation("bun create user/repo app"),
+            Operation::Other { .. }
+        ));
+        assert!(matches!(operation("npx cowsay hi"), Operation::Dlx { .. }));
+
+        // Regular installs are not frozen installs
+        assert!(matches!(
+            operation("pnpm install"),
+            Operation::Install { frozen: false, .. }
+        ));
+        assert!(matches!(operation("npm cit"), Operation::Other { .. }));
+    }
+}
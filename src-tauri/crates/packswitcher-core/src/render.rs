@@ -0,0 +1,977 @@
+// Renders the intermediate representation in `ir` as a command for a package manager.
+
+use crate::ir::{
+    dependency_flag_spellings, initializer_to_package, is_path, runner_flag_spellings, Arg,
+    Command, DependencyFlag, Invocation, Operation, PackageSpec, Registry, RunnerFlag, SpecSource,
+    WorkspaceScope,
+};
+use crate::package_manager::PackageManager::{self, Bun, Deno, Npm, Pnpm, Yarn};
+use crate::package_manager::YarnDialect;
+use crate::shell::{self, Token};
+
+// Yarn 2+ has no global packages, global installs and removals fall back to npm
+const BERRY_GLOBAL_WARNING: &str = "yarn 2+ has no global packages, used npm instead";
+
+// Renders a command for `to_pm`. Gives `None` when the target has no equivalent at all;
+// anything that could only be translated partially is reported in `warnings`.
+pub fn render(
+    command: &Command,
+    to_pm: PackageManager,
+    yarn_dialect: YarnDialect,
+    warnings: &mut Vec<String>,
+) -> Option<String> {
+    // Workspace scope: options before the subcommand and/or flags after the arguments, or
+    // a directory to run the command in
+    let (head, scope_flags, directory) = match &command.scope {
+        Some(scope) => {
+            render_workspace_scope(scope, &command.operation, to_pm, yarn_dialect, warnings)
+        }
+        None => (to_pm.to_string(), Vec::new(), None),
+    };
+
+    // `yarn workspaces run <script>` already runs scripts, so it takes no extra `run`
+    let head_and = |subcommand: &str| {
+        if subcommand == "run" && head.ends_with(" workspaces run") {
+            head.clone()
+        } else {
+            format!("{} {}", head, subcommand)
+        }
+    };
+
+    let rendered = match &command.operation {
+        Operation::Install { frozen, args } => {
+            let subcommand = match (frozen, to_pm) {
+                (false, _) => "install",
+                (true, Npm) => "ci",
+                (true, Yarn) if yarn_dialect == YarnDialect::Berry => "install --immutable",
+                (true, Pnpm | Yarn | Bun) => "install --frozen-lockfile",
+                (true, Deno) => "install --frozen",
+            };
+            with_args(head_and(subcommand), &with_flags(args, &scope_flags))
+        }
+        Operation::Add { args, global } => render_add(
+            args,
+            *global,
+            to_pm,
+            yarn_dialect,
+            &head_and,
+            &scope_flags,
+            warnings,
+        ),
+        Operation::Remove { args, global } => {
+            // Packages are removed by name, without a registry prefix
+            let tokens: Vec<Token> = args
+                .iter()
+                .map(|arg| match arg {
+                    Arg::Package(spec) => spec.token.clone(),
+                    Arg::Dependency(_, token) | Arg::Other(token) => token.clone(),
+                })
+                .collect();
+            let head = match (global, to_pm) {
+                (true, Npm) => "npm uninstall -g".to_string(),
+                (true, Yarn) if yarn_dialect == YarnDialect::Berry => {
+                    warnings.push(BERRY_GLOBAL_WARNING.to_string());
+                    "npm uninstall -g".to_string()
+                }
+                (true, Yarn) => "yarn global remove".to_string(),
+                (true, Deno) => "deno uninstall -g".to_string(),
+                (true, Pnpm | Bun) => format!("{} remove -g", to_pm),
+                (false, Npm) => head_and("uninstall"),
+                (false, _) => head_and("remove"),
+            };
+            with_args(head, &with_flags(&tokens, &scope_flags))
+        }
+        Operation::Update { args } => {
+            // Updates are `yarn upgrade` in Yarn 1 and `yarn up` in Yarn 2+, Deno updates
+            // through `outdated`
+            let subcommand = match (to_pm, yarn_dialect) {
+                (Yarn, YarnDialect::Classic) => "upgrade",
+                (Yarn, YarnDialect::Berry) => "up",
+                (Deno, _) => "outdated --update",
+                _ => "update",
+            };
+            with_args(head_and(subcommand), &with_flags(args, &scope_flags))
+        }
+        Operation::Run {
+            invocation,
+            options,
+            script,
+            args,
+        } => {
+            let run = if to_pm == Deno { "task" } else { "run" };
+            let Some(script) = script else {
+                return Some(with_args(head_and(run), &with_flags(options, &scope_flags)));
+            };
+
+            // Yarn runs any script without `run`, npm, pnpm and bun only npm's shortcuts,
+            // except for `bun test`, which starts bun's own test runner. Deno needs `task`
+            // for everything.
+            let runs_implicitly = match to_pm {
+                Yarn => *invocation != Invocation::Run,
+                Npm | Pnpm => *invocation == Invocation::Shortcut,
+                Bun => *invocation == Invocation::Shortcut && script.value != "test",
+                Deno => false,
+            };
+
+            // Keep the scope flags ahead of anything that is passed on to the script.
+            // `deno task` takes them before the task name.
+            let mut tokens = Vec::new();
+            if to_pm == Deno {
+                tokens.extend(scope_flags.iter().cloned());
+            }
+            tokens.extend(options.iter().cloned());
+            tokens.push(script.clone());
+            if to_pm != Deno {
+                tokens.extend(scope_flags.iter().cloned());
+            }
+
+            // Script arguments: npm only forwards them after a `--` separator, while pnpm,
+            // yarn and bun pass along everything after the script name
+            if !args.is_empty() {
+                if to_pm == Npm {
+                    tokens.push(Token::new("--"));
+                }
+                tokens.extend(args.iter().cloned());
+            }
+
+            if runs_implicitly {
+                with_args(head.clone(), &tokens)
+            } else {
+                with_args(head_and(run), &tokens)
+            }
+        }
+        Operation::Exec { args } => match to_pm {
+            // npm would read options meant for the binary as its own
+            Npm if args.iter().any(|arg| arg.value.starts_with('-')) => {
+                let mut tokens = scope_flags.clone();
+                tokens.push(Token::new("--"));
+                tokens.extend(args.iter().cloned());
+                with_args(head_and("exec"), &tokens)
+            }
+            Npm | Pnpm | Yarn => with_args(head_and("exec"), &with_flags(args, &scope_flags)),
+            // bunx and Deno prefer a locally installed binary too
+            Bun | Deno => render_runner(to_pm, yarn_dialect, args),
+        },
+        Operation::Dlx {
+            yes,
+            flags,
+            packages,
+            shell_command,
+            args,
+        } => render_dlx(
+            *yes,
+            flags,
+            packages,
+            shell_command.as_ref(),
+            args,
+            to_pm,
+            yarn_dialect,
+            warnings,
+        )?,
+        Operation::Create { initializer, args } => render_create(initializer, args, to_pm)?,
+        Operation::Publish { args } => {
+            let subcommand = match to_pm {
+                // `deno publish` publishes to JSR, not to the npm registry
+                Deno => return None,
+                // Yarn 2+ publishes through `yarn npm publish`
+                Yarn if yarn_dialect == YarnDialect::Berry => "npm publish",
+                _ => "publish",
+            };
+            with_args(head_and(subcommand), &with_flags(args, &scope_flags))
+        }
+        Operation::Other { subcommand, args } => {
+            with_args(head_and(&subcommand.raw), &with_flags(args, &scope_flags))
+        }
+    };
+
+    // A subshell keeps the change of directory from reaching the rest of a compound line
+    let rendered = match directory {
+        Some(directory) => format!("(cd {} && {})", shell::quote(&directory), rendered),
+        None => rendered,
+    };
+    Some(with_args(rendered, &command.redirections))
+}
+
+// `head arg1 arg2`, or just `head` without arguments
+fn with_args(head: String, args: &[Token]) -> String {
+    if args.is_empty() {
+        head
+    } else {
+        format!("{} {}", head, shell::render(args))
+    }
+}
+
+// Adds flags to the arguments, ahead of anything after a `--` separator
+fn with_flags(args: &[Token], flags: &[Token]) -> Vec<Token> {
+    let separator = args
+        .iter()
+        .position(|arg| arg.value == "--")
+        .unwrap_or(args.len());
+    let mut args = args.to_vec();
+    args.splice(separator..separator, flags.iter().cloned());
+    args
+}
+
+fn render_add(
+    args: &[Arg],
+    global: bool,
+    to_pm: PackageManager,
+    yarn_dialect: YarnDialect,
+    head_and: &dyn Fn(&str) -> String,
+    scope_flags: &[Token],
+    warnings: &mut Vec<String>,
+) -> String {
+    let mut tokens = Vec::new();
+    let mut jsr_packages = Vec::new();
+    let mut has_npm_packages = false;
+    let mut is_dev = false;
+    let mut unsupported = Vec::new();
+
+    for arg in args {
+        match arg {
+            // `jsr:` packages have no npm spelling, the others add them with the `jsr` CLI
+            Arg::Package(PackageSpec {
+                registry: Registry::Jsr,
+                token,
+                ..
+            }) if to_pm != Deno => jsr_packages.push(token.clone()),
+            Arg::Package(spec) => {
+                has_npm_packages |= spec.registry == Registry::Npm;
+                tokens.push(render_package(spec, to_pm, yarn_dialect, warnings));
+            }
+            // Dependency-type flags are spelled differently by each package manager
+            Arg::Dependency(flag, token) => {
+                is_dev |= *flag == DependencyFlag::Dev;
+                match dependency_flag_spellings(*flag, to_pm) {
+                    Some(spellings) if spellings.contains(&token.value.as_str()) => {
+                        tokens.push(token.clone())
+                    }
+                    Some([spelling, ..]) => tokens.push(Token::new(spelling)),
+                    // The target does this by default
+                    Some([]) => {}
+                    None => unsupported.push(token.raw.clone()),
+                }
+            }
+            Arg::Other(token) => tokens.push(token.clone()),
+        }
+    }
+
+    if !unsupported.is_empty() {
+        warnings.push(format!(
+            "{} has no equivalent for {}, dropped it",
+            to_pm,
+            unsupported.join(", ")
+        ));
+    }
+
+    let mut commands = Vec::new();
+    if has_npm_packages || jsr_packages.is_empty() {
+        let head = match (global, to_pm) {
+            (true, Npm) => "npm install -g".to_string(),
+            (true, Yarn) if yarn_dialect == YarnDialect::Berry => {
+                warnings.push(BERRY_GLOBAL_WARNING.to_string());
+                "npm install -g".to_string()
+            }
+            (true, Yarn) => "yarn global add".to_string(),
+            // Deno installs packages globally as executables, which need permissions
+            (true, Deno) => "deno install -g -A".to_string(),
+            (true, Pnpm | Bun) => format!("{} add -g", to_pm),
+            (false, Npm) => head_and("install"),
+            (false, _) => head_and("add"),
+        };
+        commands.push(with_args(head, &with_flags(&tokens, scope_flags)));
+    }
+    if !jsr_packages.is_empty() {
+        let mut jsr_args = vec![Token::new("jsr"), Token::new("add")];
+        if is_dev {
+            jsr_args.push(Token::new("-D"));
+        }
+        jsr_args.extend(jsr_packages);
+        commands.push(render_runner(to_pm, yarn_dialect, &jsr_args));
+    }
+
+    commands.join(" && ")
+}
+
+// Writes a package spec the way `to_pm` reads it. Deno adds packages by specifier:
+// `react@18` -> `npm:react@18`, `jsr:@std/path`, and has no aliases, paths, URLs or git
+// repositories. The others agree on most specs, except for `link:` and `workspace:`.
+fn render_package(
+    spec: &PackageSpec,
+    to_pm: PackageManager,
+    yarn_dialect: YarnDialect,
+    warnings: &mut Vec<String>,
+) -> Token {
+    let with_prefix = |prefix: &str, token: &Token| Token {
+        raw: format!("{}{}", prefix, token.raw),
+        value: format!("{}{}", prefix, token.value),
+    };
+
+    match (&spec.source, to_pm) {
+        (_, Deno) if spec.registry == Registry::Jsr => with_prefix("jsr:", &spec.token),
+        // `pnpm add jsr:@std/path` is written as a Deno specifier already
+        (SpecSource::Registry, Deno) if spec.token.value.starts_with("jsr:") => spec.token.clone(),
+        (SpecSource::Registry, Deno) => with_prefix("npm:", &spec.token),
+        (SpecSource::Alias { name, target }, Deno) => {
+            warnings.push(format!(
+                "deno has no package aliases, added {} without the name {}",
+                target, name
+            ));
+            with_prefix("npm:", &Token::new(target))
+        }
+        (_, Deno) => {
+            warnings.push(format!(
+                "deno can't add {}, kept it as written",
+                spec.token.raw
+            ));
+            spec.token.clone()
+        }
+        // npm symlinks `file:` directories, and bun's `link:` takes the name of a package
+        // registered with `bun link` rather than a path
+        (SpecSource::Link { target }, Npm | Bun) if is_path(target) => {
+            Token::new(&spec.token.value.replacen("link:", "file:", 1))
+        }
+        (SpecSource::Link { target }, _) if !is_path(target) && to_pm != Bun => {
+            warnings.push(format!(
+                "{} can't add link:{}, a package registered with `bun link`, kept it as written",
+                to_pm, target
+            ));
+            spec.token.clone()
+        }
+        // npm and Yarn 1 have no `workspace:` protocol, but link workspace packages by name
+        (SpecSource::Workspace { name }, Npm) => Token::new(name),
+        (SpecSource::Workspace { name }, Yarn) if yarn_dialect == YarnDialect::Classic => {
+            Token::new(name)
+        }
+        _ => spec.token.clone(),
+    }
+}
+
+// Runs a package with the runner options spelled the way `to_pm` takes them. Gives `None`
+// for a shell command (`-c`) the target can't run, because it has no shell mode and the
+// command is more than a single simple command.
+#[allow(clippy::too_many_arguments)]
+fn render_dlx(
+    yes: bool,
+    flags: &[(RunnerFlag, Token)],
+    packages: &[Token],
+    shell_command: Option<&Token>,
+    args: &[Token],
+    to_pm: PackageManager,
+    yarn_dialect: YarnDialect,
+    warnings: &mut Vec<String>,
+) -> Option<String> {
+    // Yarn 1 has no runner of its own and uses npx
+    let runner = match to_pm {
+        Yarn if yarn_dialect == YarnDialect::Classic => Npm,
+        pm => pm,
+    };
+
+    let mut tokens = Vec::new();
+    // The other runners install without asking
+    if yes && runner == Npm {
+        tokens.push(Token::new("--yes"));
+    }
+
+    let mut unsupported = Vec::new();
+    for (flag, token) in flags {
+        match runner_flag_spellings(*flag, runner) {
+            Some(spellings) if spellings.contains(&token.value.as_str()) => {
+                tokens.push(token.clone())
+            }
+            Some([spelling, ..]) => tokens.push(Token::new(spelling)),
+            _ => unsupported.push(token.raw.clone()),
+        }
+    }
+    if !unsupported.is_empty() {
+        warnings.push(format!(
+            "{} has no equivalent for {}, dropped it",
+            to_pm,
+            unsupported.join(", ")
+        ));
+    }
+
+    let package_flag = match runner {
+        Npm | Yarn | Bun => "-p",
+        Pnpm => "--package",
+        Deno => "",
+    };
+    if package_flag.is_empty() && !packages.is_empty() {
+        warnings.push(format!("{} has no equivalent for -p, dropped it", to_pm));
+    } else {
+        for package in packages {
+            tokens.push(Token::new(package_flag));
+            tokens.push(package.clone());
+        }
+    }
+
+    let mut args = args.to_vec();
+    match (shell_command, runner) {
+        (Some(command), Npm | Pnpm) => {
+            tokens.push(Token::new("-c"));
+            tokens.push(command.clone());
+        }
+        // Without a shell, the words of a simple command become the package to run and its
+        // arguments
+        (Some(command), _) => {
+            let is_simple = shell::split_commands(&command.value).is_ok_and(|c| c.len() == 1);
+            if !is_simple {
+                return None;
+            }
+            args.splice(0..0, shell::tokenize(&command.value).ok()?);
+        }
+        (None, _) => {}
+    }
+
+    tokens.extend(args);
+    Some(render_runner(to_pm, yarn_dialect, &tokens))
+}
+
+// Runs a package without installing it
+fn render_runner(pm: PackageManager, yarn_dialect: YarnDialect, args: &[Token]) -> String {
+    match pm {
+        Npm => with_args("npx".to_string(), args),
+        Pnpm => with_args("pnpx".to_string(), args),
+        Bun => with_args("bunx".to_string(), args),
+        // `yarn dlx` only exists in Yarn 2+
+        Yarn if yarn_dialect == YarnDialect::Berry => with_args("yarn dlx".to_string(), args),
+        Yarn => with_args("npx".to_string(), args), // Yarn 1 has no runner, fall back to npx
+        Deno => with_args("deno run -A".to_string(), &with_npm_specifier(args)),
+    }
+}
+
+// Turns runner arguments into `deno run` arguments: `-y cowsay hello` -> `npm:cowsay hello`
+fn with_npm_specifier(args: &[Token]) -> Vec<Token> {
+    // Deno never prompts before running a package, and takes its options before it
+    let options = args
+        .iter()
+        .take_while(|token| token.value.starts_with('-'))
+        .count();
+    let mut tokens: Vec<Token> = args[..options]
+        .iter()
+        .filter(|token| !["-y", "--yes"].contains(&token.value.as_str()))
+        .cloned()
+        .collect();
+    let package = tokens.len();
+    tokens.extend(args[options..].iter().cloned());
+    if let Some(package) = tokens.get_mut(package) {
+        let has_specifier = package.value.starts_with("npm:") || package.value.starts_with("jsr:");
+        if !has_specifier {
+            *package = Token {
+                raw: format!("npm:{}", package.raw),
+                value: format!("npm:{}", package.value),
+            };
+        }
+    }
+    tokens
+}
+
+fn render_create(initializer: &str, args: &[Token], to_pm: PackageManager) -> Option<String> {
+    let args = match to_pm {
+        // npm forwards positional arguments, but options need a `--` in front of them
+        Npm => {
+            let first_option = args
+                .iter()
+                .position(|arg| arg.value.starts_with('-'))
+                .unwrap_or(args.len());
+            let mut args = args.to_vec();
+            if first_option < args.len() {
+                args.insert(first_option, Token::new("--"));
+            }
+            args
+        }
+        Pnpm | Yarn | Bun => args.to_vec(),
+        // Deno has no `create`, it runs the initializer package directly
+        Deno => {
+            let package = initializer_to_package(initializer)?;
+            let mut deno_args = vec![Token::new(&format!("npm:{}", package))];
+            deno_args.extend(args.iter().cloned());
+            return Some(with_args("deno run -A".to_string(), &deno_args));
+        }
+    };
+
+    Some(with_args(
+        format!("{} create {}", to_pm, shell::quote(initializer)),
+        &args,
+    ))
+}
+
+// Renders a workspace scope for the target package manager, returning the command head
+// (everything up to the subcommand), the flags that go after the arguments and the
+// directory to run the command in, for targets that can only change dependencies there
+fn render_workspace_scope(
+    scope: &WorkspaceScope,
+    operation: &Operation,
+    to_pm: PackageManager,
+    yarn_dialect: YarnDialect,
+    warnings: &mut Vec<String>,
+) -> (String, Vec<Token>, Option<String>) {
+    let is_dependency_change = matches!(
+        operation,
+        Operation::Install { .. } | Operation::Add { .. } | Operation::Remove { .. }
+    );
+    let is_run = matches!(operation, Operation::Run { .. });
+    let is_path =
+        |name: &str| name.starts_with('.') || (name.contains('/') && !name.starts_with('@'));
+    // pnpm and bun only read a selector as a path when it starts with `.`
+    let as_selector = |name: &str| {
+        if is_path(name) && !name.starts_with('.') {
+            format!("./{}", name)
+        } else {
+            name.to_string()
+        }
+    };
+    // Selectors like `web...`, `{apps/**}`, `[origin/main]` or `!web` only work in pnpm and bun
+    let is_filter_selector = |name: &str| {
+        ["...", "{", "[", "*", "!"]
+            .iter()
+            .any(|pattern| name.contains(pattern))
+    };
+
+    let mut head = to_pm.to_string();
+    let mut flags = Vec::new();
+    let mut directory = None;
+
+    match (scope, to_pm) {
+        (WorkspaceScope::Named(names), Pnpm) => {
+            for name in names {
+                head.push_str(&format!(" --filter {}", shell::quote(&as_selector(name))));
+            }
+        }
+        (WorkspaceScope::Named(names), Npm) => {
+            for name in names {
+                if is_filter_selector(name) {
+                    warnings.push(format!("npm has no equivalent for the selector `{}`", name));
+                }
+                flags.push(Token::new("-w"));
+                flags.push(Token::new(name));
+            }
+        }
+        (WorkspaceScope::Named(names), Yarn) => {
+            for name in names {
+                if is_path(name) || is_filter_selector(name) {
+                    warnings.push(format!(
+                        "yarn selects workspaces by package name, `{}` may need to be replaced",
+                        name
+                    ));
+                }
+            }
+            match (names.as_slice(), yarn_dialect) {
+                ([name], _) => head.push_str(&format!(" workspace {}", shell::quote(name))),
+                (_, YarnDialect::Berry) => {
+                    head.push_str(" workspaces foreach -A");
+                    for name in names {
+                        head.push_str(&format!(" --include {}", shell::quote(name)));
+                    }
+                }
+                (_, YarnDialect::Classic) => {
+                    warnings.push(format!(
+                        "Yarn 1 can only target one workspace, kept `{}`",
+                        names[0]
+                    ));
+                    head.push_str(&format!(" workspace {}", shell::quote(&names[0])));
+                }
+            }
+        }
+        (WorkspaceScope::Named(names), Bun) => match names.as_slice() {
+            // `bun add` has no filter, but with a path we can run it from the workspace
+            [name] if is_dependency_change && is_path(name) => {
+                warnings.push(format!(
+                    "bun cannot add or remove dependencies of another workspace, ran it from {}",
+                    name
+                ));
+                directory = Some(name.clone());
+            }
+            _ if is_dependency_change => warnings.push(format!(
+                "bun cannot add or remove dependencies of another workspace, run it from {}",
+                names.join(", ")
+            )),
+            _ => {
+                for name in names {
+                    head.push_str(&format!(" --filter {}", shell::quote(&as_selector(name))));
+                }
+            }
+        },
+        (WorkspaceScope::Named(names), Deno) => match names.as_slice() {
+            [name] if is_dependency_change && is_path(name) => {
+                warnings.push(format!(
+                    "deno cannot add or remove dependencies of another workspace, ran it from {}",
+                    name
+                ));
+                directory = Some(name.clone());
+            }
+            _ if is_dependency_change => warnings.push(format!(
+                "deno cannot add or remove dependencies of another workspace, run it from {}",
+                names.join(", ")
+            )),
+            _ => {
+                if names.len() > 1 {
+                    warnings.push(format!(
+                        "deno task takes a single --filter, kept `{}`",
+                        names[0]
+                    ));
+                }
+                flags.push(Token::new("--filter"));
+                flags.push(Token::new(&names[0]));
+            }
+        },
+        (WorkspaceScope::All, Pnpm) => head.push_str(" -r"),
+        (WorkspaceScope::All, Npm) => flags.push(Token::new("--workspaces")),
+        // `yarn workspaces run` only runs scripts
+        (WorkspaceScope::All, Yarn) if is_run && yarn_dialect == YarnDialect::Classic => {
+            head.push_str(" workspaces run")
+        }
+        (WorkspaceScope::All, Yarn) if yarn_dialect == YarnDialect::Berry => {
+            head.push_str(" workspaces foreach -A")
+        }
+        (WorkspaceScope::All, Bun) => head.push_str(" --filter '*'"),
+        (WorkspaceScope::All, Deno) if !is_dependency_change => {
+            flags.push(Token::new("--recursive"))
+        }
+        (WorkspaceScope::All, _) => warnings.push(format!(
+            "{} cannot run this command in every workspace at once, run it in each of them",
+            to_pm
+        )),
+        (WorkspaceScope::Root, Pnpm) if is_dependency_change => flags.push(Token::new("-w")),
+        (WorkspaceScope::Root, Pnpm) => head.push_str(" -w"),
+        (WorkspaceScope::Root, Yarn)
+            if is_dependency_change && yarn_dialect == YarnDialect::Classic =>
+        {
+            flags.push(Token::new("-W"))
+        }
+        (WorkspaceScope::Root, _) => warnings.push(format!(
+            "{} has no option for the workspace root, run it from the root of the project",
+            to_pm
+        )),
+    }
+
+    (head, flags, directory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse;
+
+    fn render_to(command: &str, to_pm: PackageManager, yarn_dialect: YarnDialect) -> String {
+        render(
+            &parse(command).unwrap(),
+            to_pm,
+            yarn_dialect,
+            &mut Vec::new(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_render_runner() {
+        let args = [Token::new("create-next-app")];
+        assert_eq!(
+            render_runner(Npm, YarnDialect::Berry, &args),
+            "npx create-next-app"
+        );
+        assert_eq!(
+            render_runner(Pnpm, YarnDialect::Berry, &args),
+            "pnpx create-next-app"
+        );
+        assert_eq!(
+            render_runner(Yarn, YarnDialect::Berry, &args),
+            "yarn dlx create-next-app"
+        );
+        assert_eq!(
+            render_runner(Yarn, YarnDialect::Classic, &args),
+            "npx create-next-app"
+        );
+        assert_eq!(
+            render_runner(Bun, YarnDialect::Berry, &args),
+            "bunx create-next-app"
+        );
+        assert_eq!(
+            render_runner(
+                Deno,
+                YarnDialect::Berry,
+                &[Token::new("-y"), Token::new("cowsay")]
+            ),
+            "deno run -A npm:cowsay"
+        );
+    }
+
+    #[test]
+    fn test_runner_options() {
+        let command = "npx --yes -p typescript -p ts-node ts-node script.ts";
+        assert_eq!(
+            render_to(command, Pnpm, YarnDialect::Classic),
+            "pnpx --package typescript --package ts-node ts-node script.ts"
+        );
+        assert_eq!(
+            render_to(command, Yarn, YarnDialect::Berry),
+            "yarn dlx -p typescript -p ts-node ts-node script.ts"
+        );
+        assert_eq!(
+            render_to(command, Yarn, YarnDialect::Classic),
+            "npx --yes -p typescript -p ts-node ts-node script.ts"
+        );
+        assert_eq!(
+            render_to(
+                "yarn dlx -p typescript tsc --init",
+                Bun,
+                YarnDialect::Classic
+            ),
+            "bunx -p typescript tsc --init"
+        );
+
+        // A shell command stays one where the target has a shell mode
+        assert_eq!(
+            render_to("npx -c 'eslint .'", Pnpm, YarnDialect::Classic),
+            "pnpx -c 'eslint .'"
+        );
+        assert_eq!(
+            render_to("pnpx -c 'eslint .'", Npm, YarnDialect::Classic),
+            "npx -c 'eslint .'"
+        );
+        assert_eq!(
+            render_to("npx --call='eslint .'", Bun, YarnDialect::Classic),
+            "bunx eslint ."
+        );
+
+        // Each runner's own options are respelled, or dropped with a warning
+        assert_eq!(
+            render_to("pnpm dlx --silent cowsay hi", Yarn, YarnDialect::Berry),
+            "yarn dlx --quiet cowsay hi"
+        );
+        assert_eq!(
+            render_to("npx --no tsc", Bun, YarnDialect::Classic),
+            "bunx --no-install tsc"
+        );
+        assert_eq!(
+            render_to("bunx --silent cowsay", Deno, YarnDialect::Classic),
+            "deno run -A --quiet npm:cowsay"
+        );
+        assert_eq!(
+            render_to("deno run -A -q npm:cowsay", Npm, YarnDialect::Classic),
+            "npx -q cowsay"
+        );
+        let mut warnings = Vec::new();
+        let command = parse("bunx --bun vite").unwrap();
+        assert_eq!(
+            render(&command, Npm, YarnDialect::Classic, &mut warnings),
+            Some("npx vite".to_string())
+        );
+        assert_eq!(
+            warnings,
+            vec!["npm has no equivalent for --bun, dropped it"]
+        );
+
+        // `--yes` doesn't keep `npx -y create-vite` from being a `create`
+        assert_eq!(
+            render_to("npx --yes create-vite my-app", Pnpm, YarnDialect::Classic),
+            "pnpm create vite my-app"
+        );
+
+        let mut warnings = Vec::new();
+        let command = parse("npx -p cowsay -c 'echo hi'").unwrap();
+        assert_eq!(
+            render(&command, Deno, YarnDialect::Classic, &mut warnings),
+            Some("deno run -A npm:echo hi".to_string())
+        );
+        assert_eq!(warnings, vec!["deno has no equivalent for -p, dropped it"]);
+
+        // Without a shell mode, only a single simple command can run
+        for to in [
+            (Bun, YarnDialect::Classic),
+            (Yarn, YarnDialect::Berry),
+            (Deno, YarnDialect::Classic),
+        ] {
+            let command = parse("npx -c 'eslint . && prettier .'").unwrap();
+            assert_eq!(render(&command, to.0, to.1, &mut Vec::new()), None);
+        }
+    }
+
+    #[test]
+    fn test_render_warnings() {
+        let mut warnings = Vec::new();
+        let command = parse("yarn add -T lodash").unwrap();
+        assert_eq!(
+            render(&command, Npm, YarnDialect::Classic, &mut warnings),
+            Some("npm install lodash".to_string())
+        );
+        assert_eq!(warnings, vec!["npm has no equivalent for -T, dropped it"]);
+    }
+
+    #[test]
+    fn test_package_specs() {
+        // Aliases, git repositories, tarballs and paths read the same everywhere but Deno
+        for spec in [
+            "my-react@npm:react@18",
+            "github:user/repo#branch",
+            "user/repo",
+            "./pkg.tgz",
+            "https://example.com/pkg.tgz",
+            "file:../lib",
+            "../lib",
+        ] {
+            for to_pm in [Npm, Pnpm, Yarn, Bun] {
+                let expected = match to_pm {
+                    Npm => format!("npm install {}", spec),
+                    _ => format!("{} add {}", to_pm, spec),
+                };
+                assert_eq!(
+                    render_to(&format!("npm install {}", spec), to_pm, YarnDialect::Berry),
+                    expected
+                );
+            }
+        }
+
+        // `link:` takes a path everywhere but in bun, which wants `file:`
+        assert_eq!(
+            render_to("pnpm add link:../lib", Bun, YarnDialect::Classic),
+            "bun add file:../lib"
+        );
+        assert_eq!(
+            render_to("yarn add lib@link:../lib", Npm, YarnDialect::Classic),
+            "npm install lib@file:../lib"
+        );
+        assert_eq!(
+            render_to("pnpm add link:../lib", Yarn, YarnDialect::Berry),
+            "yarn add link:../lib"
+        );
+
+        // `workspace:` is left out for the package managers that link workspaces by name
+        assert_eq!(
+            render_to("pnpm add my-lib@workspace:*", Npm, YarnDialect::Classic),
+            "npm install my-lib"
+        );
+        assert_eq!(
+            render_to("pnpm add my-lib@workspace:^", Yarn, YarnDialect::Classic),
+            "yarn add my-lib"
+        );
+        assert_eq!(
+            render_to("pnpm add my-lib@workspace:^", Yarn, YarnDialect::Berry),
+            "yarn add my-lib@workspace:^"
+        );
+        assert_eq!(
+            render_to("bun add my-lib@workspace:*", Pnpm, YarnDialect::Classic),
+            "pnpm add my-lib@workspace:*"
+        );
+
+        let mut warnings = Vec::new();
+        let command = parse("bun add link:my-lib my-react@npm:react@18 ./pkg.tgz").unwrap();
+        assert_eq!(
+            render(&command, Pnpm, YarnDialect::Classic, &mut warnings),
+            Some("pnpm add link:my-lib my-react@npm:react@18 ./pkg.tgz".to_string())
+        );
+        assert_eq!(
+            warnings,
+            vec!["pnpm can't add link:my-lib, a package registered with `bun link`, kept it as written"]
+        );
+
+        let mut warnings = Vec::new();
+        assert_eq!(
+            render(&command, Deno, YarnDialect::Classic, &mut warnings),
+            Some("deno add link:my-lib npm:react@18 ./pkg.tgz".to_string())
+        );
+        assert_eq!(
+            warnings,
+            vec![
+                "deno can't add link:my-lib, kept it as written",
+                "deno has no package aliases, added react@18 without the name my-react",
+                "deno can't add ./pkg.tgz, kept it as written",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exec() {
+        assert_eq!(
+            render_to("pnpm exec tsc --noEmit", Npm, YarnDialect::Classic),
+            "npm exec -- tsc --noEmit"
+        );
+        assert_eq!(
+            render_to("npm exec -- eslint .", Yarn, YarnDialect::Berry),
+            "yarn exec eslint ."
+        );
+        assert_eq!(
+            render_to("yarn exec prisma generate", Bun, YarnDialect::Classic),
+            "bunx prisma generate"
+        );
+    }
+
+    const ROUND_TRIP_COMMANDS: &[&str] = &[
+        "npm install",
+        "npm i -D vitest",
+        "npm install -g typescript",
+        "npm uninstall react",
+        "npm ci --ignore-scripts",
+        "npm run build",
+        "npm run --silent dev -- --port 3000",
+        "npm test -- --watch",
+        "npm update react",
+        "npm publish --access public",
+        "npm exec -- eslint .",
+        "npm create vite@latest my-app -- --template react",
+        "npm install zod -w apps/web",
+        "npm run test --workspaces",
+        "npm audit fix",
+        "npx cowsay hello",
+        "npx -y -p typescript -p ts-node ts-node script.ts",
+        "npx --package=eslint -c 'eslint .'",
+        "pnpm dlx --package cowsay -c 'echo hi | cowsay'",
+        "pnpm add -O fsevents",
+        "pnpm --filter web run dev",
+        "pnpm -r run build",
+        "pnpm add -w -D typescript",
+        "pnpm install --frozen-lockfile",
+        "yarn",
+        "yarn build --watch",
+        "yarn add -P react",
+        "yarn global add typescript",
+        "yarn workspace web add zod",
+        "yarn upgrade react",
+        "bun add --optional fsevents",
+        "bun --filter web run dev",
+        "bun create vite my-app",
+        "bunx prisma generate",
+        "deno add --dev npm:vitest jsr:@std/path",
+        "deno task --filter web dev",
+        "deno install -g -A npm:typescript",
+        "deno outdated --update react",
+        "deno run -A npm:cowsay hello",
+    ];
+
+    #[test]
+    fn test_round_trips() {
+        for command in ROUND_TRIP_COMMANDS {
+            let parsed = parse(command).unwrap();
+
+            // Rendering a command for its own package manager gives back the same command
+            let rendered = render(&parsed, parsed.pm, YarnDialect::Classic, &mut Vec::new());
+            assert_eq!(
+                rendered.as_deref().and_then(parse),
+                Some(parsed.clone()),
+                "{} -> {:?}",
+                command,
+                rendered
+            );
+
+            // Translating a translation again changes nothing
+            for to_pm in PackageManager::ALL {
+                for yarn_dialect in [YarnDialect::Classic, YarnDialect::Berry] {
+                    let Some(translated) = render(&parsed, to_pm, yarn_dialect, &mut Vec::new())
+                    else {
+                        continue;
+                    };
+                    let Some(reparsed) = parse(&translated) else {
+                        continue;
+                    };
+                    assert_eq!(
+                        render(&reparsed, to_pm, yarn_dialect, &mut Vec::new()).as_ref(),
+                        Some(&translated),
+                        "{} -> {}",
+                        command,
+                        translated
+                    );
+                }
+            }
+        }
+    }
+}
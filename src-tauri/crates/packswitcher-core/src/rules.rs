@@ -0,0 +1,392 @@
+// Translation rules: equivalent spellings of a command across package managers, for
+// commands that `parse` doesn't know or translates differently than a team wants.
+//
+// Users can add rules in `rules.toml` (or `rules.json`) in the app config dir:
+//
+//     [[rules]]
+//     npm = "audit fix"
+//     pnpm = "audit --fix"
+//
+// Each value is the command without the package manager's name. Arguments after a
+// matching command are passed along. User rules win over the built-in ones, and both win
+// over the regular translation.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::package_manager::PackageManager::{self, Bun, Npm, Pnpm, Yarn};
+use crate::shell::{self, Token};
+
+/// A rule read with [`load_rules`] or [`parse_rules`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    // How the rule is shown to the user: `rule 2` for the second rule of the file
+    name: String,
+    // The command for each package manager the rule covers, in `PackageManager::ALL` order
+    commands: Vec<(PackageManager, Vec<Token>)>,
+}
+
+// A command translated by a rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTranslation {
+    pub from: PackageManager,
+    pub translated: String,
+    // The name of the rule that matched
+    pub rule: String,
+}
+
+// Commands with a different name (or none) per package manager
+const BUILTIN_RULES: &[&[(PackageManager, &str)]] = &[
+    &[(Npm, "ls"), (Pnpm, "list"), (Yarn, "list"), (Bun, "pm ls")],
+    &[(Npm, "explain"), (Pnpm, "why"), (Yarn, "why"), (Bun, "why")],
+    &[(Npm, "audit fix"), (Pnpm, "audit --fix")],
+    &[
+        (Npm, "cache clean --force"),
+        (Pnpm, "store prune"),
+        (Yarn, "cache clean"),
+        (Bun, "pm cache rm"),
+    ],
+];
+
+impl Rule {
+    // The number of arguments this rule's command for `from_pm` takes up, if the arguments
+    // start with it
+    fn matches(&self, from_pm: PackageManager, args: &[Token]) -> Option<usize> {
+        let (_, from_command) = self.commands.iter().find(|(pm, _)| *pm == from_pm)?;
+        let matches = args.len() >= from_command.len()
+            && from_command
+                .iter()
+                .zip(args)
+                .all(|(expected, arg)| expected.value == arg.value);
+        matches.then_some(from_command.len())
+    }
+
+    // Translates `<from_pm> <args>` if the arguments start with this rule's command for
+    // `from_pm` and the rule has a command for `to_pm`
+    fn apply(
+        &self,
+        from_pm: PackageManager,
+        args: &[Token],
+        to_pm: PackageManager,
+    ) -> Option<RuleTranslation> {
+        let matched = self.matches(from_pm, args)?;
+        let (_, to_command) = self.commands.iter().find(|(pm, _)| *pm == to_pm)?;
+
+        let mut tokens = to_command.clone();
+        tokens.extend_from_slice(&args[matched..]);
+        Some(RuleTranslation {
+            from: from_pm,
+            translated: format!("{} {}", to_pm, shell::render(&tokens)),
+            rule: self.name.clone(),
+        })
+    }
+}
+
+fn builtin_rules() -> Vec<Rule> {
+    BUILTIN_RULES
+        .iter()
+        .map(|commands| Rule {
+            name: format!("built-in rule `{} {}`", commands[0].0, commands[0].1),
+            commands: commands
+                .iter()
+                .map(|(pm, command)| (*pm, shell::tokenize(command).unwrap()))
+                .collect(),
+        })
+        .collect()
+}
+
+// Translates a simple command with the first matching rule, trying the user's rules before
+// the built-in ones. A built-in rule that knows the command but not the target is reported
+// in `warnings`.
+pub fn translate(
+    user_rules: &[Rule],
+    command: &str,
+    to_pm: PackageManager,
+    warnings: &mut Vec<String>,
+) -> Option<RuleTranslation> {
+    // Redirections aren't part of the command a rule matches, and stay at the end
+    let (redirections, tokens): (Vec<Token>, Vec<Token>) = shell::tokenize(command)
+        .ok()?
+        .into_iter()
+        .partition(Token::is_redirection);
+    let (program, args) = tokens.split_first()?;
+    let from_pm: PackageManager = program.value.parse().ok()?;
+    if from_pm == to_pm {
+        return None;
+    }
+
+    let builtin_rules = builtin_rules();
+    let translation = user_rules
+        .iter()
+        .chain(&builtin_rules)
+        .find_map(|rule| rule.apply(from_pm, args, to_pm))
+        .map(|mut translation| {
+            for redirection in &redirections {
+                translation.translated.push(' ');
+                translation.translated.push_str(&redirection.raw);
+            }
+            translation
+        });
+
+    if translation.is_none() {
+        if let Some(matched) = builtin_rules
+            .iter()
+            .find_map(|rule| rule.matches(from_pm, args))
+        {
+            warnings.push(format!(
+                "{} has no equivalent for `{} {}`",
+                to_pm,
+                from_pm,
+                shell::render(&args[..matched])
+            ));
+        }
+    }
+
+    translation
+}
+
+/// The formats rules can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RulesFormat {
+    Toml,
+    Json,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    // Keyed by name rather than `PackageManager`, so that an unknown name is reported with
+    // its rule number
+    rules: Vec<HashMap<String, String>>,
+}
+
+/// Loads the user's rules from `rules.toml` or `rules.json` in `config_dir`. No file means
+/// no rules.
+pub fn load_rules(config_dir: &Path) -> Result<Vec<Rule>, String> {
+    let toml_path = config_dir.join("rules.toml");
+    let json_path = config_dir.join("rules.json");
+
+    let (path, format) = match (toml_path.exists(), json_path.exists()) {
+        (true, true) => {
+            return Err(format!(
+                "Found both {} and {}, please keep only one of them",
+                toml_path.display(),
+                json_path.display()
+            ))
+        }
+        (true, false) => (toml_path, RulesFormat::Toml),
+        (false, true) => (json_path, RulesFormat::Json),
+        (false, false) => return Ok(Vec::new()),
+    };
+
+    let text = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_rules(&text, format).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Parses rules from the contents of a rules file. Each rule needs commands for at least
+/// two package managers.
+pub fn parse_rules(text: &str, format: RulesFormat) -> Result<Vec<Rule>, String> {
+    let file: RulesFile = match format {
+        RulesFormat::Toml => toml::from_str(text).map_err(|e| e.to_string())?,
+        RulesFormat::Json => serde_json::from_str(text).map_err(|e| e.to_string())?,
+    };
+
+    file.rules
+        .iter()
+        .enumerate()
+        .map(|(i, commands)| validate_rule(i + 1, commands))
+        .collect()
+}
+
+fn validate_rule(number: usize, commands: &HashMap<String, String>) -> Result<Rule, String> {
+    let commands = commands
+        .iter()
+        .map(|(name, command)| match name.parse::<PackageManager>() {
+            Ok(pm) => Ok((pm, command)),
+            Err(e) => Err(format!(
+                "rule {}: unknown package manager `{}`, expected one of {}",
+                number,
+                e.value,
+                e.expected
+                    .iter()
+                    .map(|pm| pm.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    if commands.len() < 2 {
+        return Err(format!(
+            "rule {} needs commands for at least two package managers",
+            number
+        ));
+    }
+
+    let mut rule = Rule {
+        name: format!("rule {}", number),
+        commands: Vec::new(),
+    };
+    for pm in PackageManager::ALL {
+        let Some(command) = commands.get(&pm) else {
+            continue;
+        };
+        let tokens = shell::tokenize(command).map_err(|e| {
+            format!(
+                "rule {}: the {} command `{}` is not valid: {}",
+                number, pm, command, e
+            )
+        })?;
+        match tokens.first() {
+            None => {
+                return Err(format!("rule {}: the {} command is empty", number, pm));
+            }
+            // A common slip: `npm = "npm audit fix"`
+            Some(first) if first.value == pm.as_str() => {
+                return Err(format!(
+                    "rule {}: leave `{}` out of the {} command, write `{}`",
+                    number,
+                    pm,
+                    pm,
+                    shell::render(&tokens[1..])
+                ));
+            }
+            Some(_) => rule.commands.push((pm, tokens)),
+        }
+    }
+
+    Ok(rule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_manager::PackageManager::Deno;
+
+    // The translated text of a command, if a rule matched
+    fn translate_text(rules: &[Rule], command: &str, to_pm: PackageManager) -> Option<String> {
+        translate(rules, command, to_pm, &mut Vec::new()).map(|translation| translation.translated)
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        assert_eq!(
+            translate_text(&[], "npm audit fix --force", Pnpm),
+            Some("pnpm audit --fix --force".to_string())
+        );
+        assert_eq!(
+            translate_text(&[], "pnpm list --depth 0", Bun),
+            Some("bun pm ls --depth 0".to_string())
+        );
+        assert_eq!(
+            translate_text(&[], "yarn why react", Npm),
+            Some("npm explain react".to_string())
+        );
+
+        // Package managers a rule doesn't cover fall back to the regular translation
+        assert_eq!(translate_text(&[], "npm audit fix", Bun), None);
+        assert_eq!(translate_text(&[], "npm ls", Deno), None);
+        assert_eq!(translate_text(&[], "npm audit", Pnpm), None);
+        assert_eq!(translate_text(&[], "npm ls", Npm), None);
+
+        let mut warnings = Vec::new();
+        assert_eq!(
+            translate(&[], "npm audit fix --force", Bun, &mut warnings),
+            None
+        );
+        assert_eq!(warnings, vec!["bun has no equivalent for `npm audit fix`"]);
+    }
+
+    #[test]
+    fn test_user_rules() {
+        let rules = parse_rules(
+            r#"
+[[rules]]
+npm = "audit fix"
+bun = "audit"
+
+[[rules]]
+pnpm = "deploy"
+npm = "run deploy"
+"#,
+            RulesFormat::Toml,
+        )
+        .unwrap();
+
+        // User rules extend and override the built-in ones
+        assert_eq!(
+            translate_text(&rules, "npm audit fix", Bun),
+            Some("bun audit".to_string())
+        );
+        assert_eq!(
+            translate_text(&rules, "npm audit fix", Pnpm),
+            Some("pnpm audit --fix".to_string())
+        );
+        assert_eq!(
+            translate(&rules, "pnpm deploy --prod dist", Npm, &mut Vec::new()),
+            Some(RuleTranslation {
+                from: Pnpm,
+                translated: "npm run deploy --prod dist".to_string(),
+                rule: "rule 2".to_string(),
+            })
+        );
+
+        let json_rules = parse_rules(
+            r#"{ "rules": [{ "npm": "audit fix", "bun": "audit" }] }"#,
+            RulesFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(json_rules, rules[..1]);
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let error = |text: &str| parse_rules(text, RulesFormat::Toml).unwrap_err();
+
+        assert_eq!(
+            error("[[rules]]\nnpm = \"ls\""),
+            "rule 1 needs commands for at least two package managers"
+        );
+        assert_eq!(
+            error("[[rules]]\nnpm = \"ls\"\npnpm = \"pnpm list\""),
+            "rule 1: leave `pnpm` out of the pnpm command, write `list`"
+        );
+        assert_eq!(
+            error("[[rules]]\nnpm = \"ls\"\nbun = \"\""),
+            "rule 1: the bun command is empty"
+        );
+        assert!(error("[[rules]]\nnpm = \"ls\"\nbun = \"pm 'ls\"")
+            .starts_with("rule 1: the bun command `pm 'ls` is not valid"));
+        assert_eq!(
+            error("[[rules]]\nnpm = \"ls\"\nbun = \"pm ls\"\n\n[[rules]]\nnpm = \"ls\"\npnmp = \"list\""),
+            "rule 2: unknown package manager `pnmp`, expected one of npm, pnpm, yarn, bun, deno"
+        );
+        // Syntax errors keep their line
+        let syntax_error = error("[[rules]]\nnpm = \"ls\"\nbun = pm ls");
+        assert!(syntax_error.contains("line 3"), "{}", syntax_error);
+        assert!(error("[[rule]]\nnpm = \"ls\"").contains("unknown field `rule`"));
+        assert!(parse_rules("{ \"rules\": {} }", RulesFormat::Json).is_err());
+    }
+
+    #[test]
+    fn test_load_rules() {
+        let dir = std::env::temp_dir().join(format!("packswitcher-rules-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(load_rules(&dir), Ok(Vec::new()));
+
+        fs::write(
+            dir.join("rules.json"),
+            r#"{ "rules": [{ "npm": "ls", "deno": "info" }] }"#,
+        )
+        .unwrap();
+        assert_eq!(load_rules(&dir).unwrap().len(), 1);
+
+        fs::write(dir.join("rules.toml"), "").unwrap();
+        assert!(load_rules(&dir).unwrap_err().starts_with("Found both"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,550 @@
+// POSIX-shell style tokenizer used when translating copied commands.
+//
+// Tokens keep the exact text they were written with, so quotes, escapes and
+// `$VAR` expansions survive a translation untouched. Tokens produced by the
+// translator itself are quoted only when the shell would otherwise split or
+// expand them.
+
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// The token as it appeared in the source, quotes and escapes included
+    pub raw: String,
+    /// The token after quote removal, used for matching subcommands and flags
+    pub value: String,
+}
+
+impl Token {
+    pub fn new(value: &str) -> Self {
+        Token {
+            raw: quote(value),
+            value: value.to_string(),
+        }
+    }
+
+    pub fn is_operator(&self) -> bool {
+        self.raw == "\n" || OPERATORS.contains(&self.raw.as_str())
+    }
+
+    /// Whether the token is a redirection together with its target, like `> /dev/null`
+    /// or `2>&1`
+    pub fn is_redirection(&self) -> bool {
+        placeholder_len(&self.raw).is_none() && redirection_len(&self.raw).is_some()
+    }
+}
+
+// Unquoted characters that end a simple command
+const OPERATOR_CHARS: [char; 5] = ['&', '|', ';', '(', ')'];
+
+// Multi-character operators, longest first
+const OPERATORS: [&str; 9] = ["&&", "||", ";;", "|&", "&", "|", ";", "(", ")"];
+
+// Redirection operators, longest first
+const REDIRECTIONS: [&str; 11] = [
+    "&>>", "&>", ">>", ">&", ">|", "<<<", "<<", "<&", "<>", ">", "<",
+];
+
+// The length of the redirection operator `input` starts with, including a file descriptor
+// number in front of it (`2>`)
+fn redirection_len(input: &str) -> Option<usize> {
+    let digits = input.len() - input.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    REDIRECTIONS
+        .iter()
+        .find(|operator| input[digits..].starts_with(*operator))
+        .map(|operator| digits + operator.len())
+}
+
+pub fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    Ok(scan(input)?.into_iter().map(|(token, _)| token).collect())
+}
+
+/// Returns the byte ranges of the simple commands in a shell line, i.e. the
+/// text between `&&`, `||`, `;`, pipes, subshell parentheses and newlines.
+pub fn split_commands(input: &str) -> Result<Vec<Range<usize>>, String> {
+    let mut commands = Vec::new();
+    let mut current: Option<Range<usize>> = None;
+
+    for (token, span) in scan(input)? {
+        if token.is_operator() {
+            commands.extend(current.take());
+        } else {
+            current = Some(match current {
+                Some(range) => range.start..span.end,
+                None => span,
+            });
+        }
+    }
+    commands.extend(current);
+
+    Ok(commands)
+}
+
+// Shell prompts that commands in docs are often shown with, as in `$ npm install`
+const PROMPTS: [&str; 2] = ["$ ", "> "];
+
+// Programs that run the command after them, with their options that take a value
+const WRAPPERS: &[(&str, &[&str])] = &[
+    (
+        "sudo",
+        &[
+            "-u", "--user", "-g", "--group", "-h", "--host", "-D", "--chdir",
+        ],
+    ),
+    ("time", &["-f", "--format", "-o", "--output"]),
+    ("env", &["-u", "--unset", "-C", "--chdir"]),
+    ("cross-env", &[]),
+];
+
+// Package runners that wrappers are often started with, as in `npx cross-env FOO=1 npm test`
+const WRAPPER_RUNNERS: [&str; 3] = ["npx", "pnpx", "bunx"];
+
+/// Removes a leading shell prompt like `$ ` or `> ` from a copied command.
+pub fn strip_prompt(input: &str) -> &str {
+    PROMPTS
+        .iter()
+        .find_map(|prompt| input.strip_prefix(prompt))
+        .map_or(input, str::trim_start)
+}
+
+/// Returns the byte offset where the command run by a simple command starts, after
+/// any environment assignments (`NODE_ENV=production`) and wrappers (`sudo`, `time`,
+/// `env`, `cross-env`, `dotenv --`) in front of it.
+pub fn command_start(input: &str) -> Result<usize, String> {
+    let tokens = scan(input)?;
+    let mut i = 0;
+
+    while let Some((token, _)) = tokens.get(i) {
+        if is_assignment(&token.raw) {
+            i += 1;
+            continue;
+        }
+
+        // A runner only counts when it starts a wrapper, `npx prisma` is the command itself
+        let mut wrapper = i;
+        if WRAPPER_RUNNERS.contains(&token.value.as_str()) {
+            wrapper += 1;
+            while tokens
+                .get(wrapper)
+                .is_some_and(|(token, _)| token.value == "-y" || token.value == "--yes")
+            {
+                wrapper += 1;
+            }
+        }
+        let Some((name, _)) = tokens.get(wrapper) else {
+            break;
+        };
+
+        // dotenv-cli takes its own options up to a `--`
+        if name.value == "dotenv" {
+            match tokens[wrapper..]
+                .iter()
+                .position(|(token, _)| token.value == "--")
+            {
+                Some(separator) => i = wrapper + separator + 1,
+                None => break,
+            }
+            continue;
+        }
+
+        let Some((_, value_options)) = WRAPPERS.iter().find(|(wrapper, _)| *wrapper == name.value)
+        else {
+            break;
+        };
+        i = wrapper + 1;
+        while let Some((option, _)) = tokens.get(i) {
+            if option.value == "--" {
+                i += 1;
+                break;
+            }
+            if !option.value.starts_with('-') {
+                break;
+            }
+            i += if value_options.contains(&option.value.as_str()) {
+                2
+            } else {
+                1
+            };
+        }
+    }
+
+    Ok(tokens.get(i).map_or(input.len(), |(_, span)| span.start))
+}
+
+// Whether a word is an environment assignment like `NODE_ENV=production`
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+// The length of the placeholder `input` starts with, like the `<package>` of
+// `npm install <package>` in docs, which would be a redirection to the shell
+fn placeholder_len(input: &str) -> Option<usize> {
+    let name = input.strip_prefix('<')?.split('>').next()?;
+    let is_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@' | '/' | ':'));
+    (is_name && input.len() > name.len() + 1).then_some(name.len() + 2)
+}
+
+/// Tokenizes a shell line, returning every token with its byte range in the input.
+pub fn scan(input: &str) -> Result<Vec<(Token, Range<usize>)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    loop {
+        // Skip the whitespace and line continuations between tokens
+        loop {
+            let mut lookahead = chars.clone();
+            match (lookahead.next(), lookahead.next()) {
+                (Some((_, c)), _) if c.is_whitespace() && c != '\n' => {
+                    chars.next();
+                }
+                (Some((_, '\\')), Some((_, '\n'))) => {
+                    chars.next();
+                    chars.next();
+                }
+                _ => break,
+            }
+        }
+
+        let Some(&(start, first)) = chars.peek() else {
+            break;
+        };
+
+        // A comment runs to the end of the line and isn't part of any token
+        if first == '#' {
+            while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            continue;
+        }
+
+        // A redirection is a token together with its target, which may follow a space
+        let mut value = String::new();
+        let redirection = match placeholder_len(&input[start..]) {
+            Some(_) => None,
+            None => redirection_len(&input[start..]),
+        };
+        if let Some(length) = redirection {
+            value.push_str(&input[start..start + length]);
+            while chars.next_if(|&(i, _)| i < start + length).is_some() {}
+            while chars.next_if(|&(_, c)| c == ' ' || c == '\t').is_some() {}
+        }
+        // Newlines and control operators are tokens of their own
+        else if first == '\n' || OPERATOR_CHARS.contains(&first) {
+            let operator = OPERATORS
+                .iter()
+                .find(|operator| input[start..].starts_with(*operator))
+                .copied()
+                .unwrap_or("\n");
+            for _ in operator.chars() {
+                chars.next();
+            }
+            let token = Token {
+                raw: operator.to_string(),
+                value: operator.to_string(),
+            };
+            tokens.push((token, start..start + operator.len()));
+            continue;
+        }
+
+        while let Some(&(i, c)) = chars.peek() {
+            if let Some(length) = placeholder_len(&input[i..]) {
+                value.push_str(&input[i..i + length]);
+                while chars.next_if(|&(j, _)| j < i + length).is_some() {}
+                continue;
+            }
+            // Unquoted, a redirection starts a new token
+            if c.is_whitespace() || OPERATOR_CHARS.contains(&c) || c == '<' || c == '>' {
+                break;
+            }
+            chars.next();
+
+            match c {
+                '\\' => match chars.next() {
+                    // A backslash-newline is a line continuation and expands to nothing
+                    Some((_, '\n')) => {}
+                    Some((_, escaped)) => value.push(escaped),
+                    None => return Err("Trailing backslash".to_string()),
+                },
+                '\'' => loop {
+                    match chars.next() {
+                        Some((_, '\'')) => break,
+                        Some((_, quoted)) => value.push(quoted),
+                        None => return Err("Unterminated single quote".to_string()),
+                    }
+                },
+                '"' => loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, '\n')) => {}
+                            // Inside double quotes only these characters can be escaped
+                            Some((_, escaped @ ('$' | '`' | '"' | '\\'))) => value.push(escaped),
+                            Some((_, other)) => {
+                                value.push('\\');
+                                value.push(other);
+                            }
+                            None => return Err("Unterminated double quote".to_string()),
+                        },
+                        Some((_, quoted)) => value.push(quoted),
+                        None => return Err("Unterminated double quote".to_string()),
+                    }
+                },
+                // Command substitutions stay part of the word they appear in
+                '$' if chars.peek().is_some_and(|&(_, next)| next == '(') => {
+                    value.push('$');
+                    let mut depth = 0;
+                    for (_, inner) in chars.by_ref() {
+                        value.push(inner);
+                        match inner {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    if depth != 0 {
+                        return Err("Unterminated command substitution".to_string());
+                    }
+                }
+                _ => value.push(c),
+            }
+        }
+
+        let end = chars.peek().map_or(input.len(), |&(i, _)| i);
+        let token = Token {
+            raw: input[start..end].to_string(),
+            value,
+        };
+        tokens.push((token, start..end));
+    }
+
+    Ok(tokens)
+}
+
+/// Quotes a value so the shell reads it back as a single, unexpanded word.
+pub fn quote(value: &str) -> String {
+    if value.is_empty() {
+        return "''".to_string();
+    }
+
+    let is_safe = value.chars().enumerate().all(|(i, c)| {
+        c.is_ascii_alphanumeric()
+            || matches!(
+                c,
+                '@' | '%' | '+' | '=' | ':' | ',' | '.' | '/' | '_' | '-' | '^'
+            )
+            || (c == '~' && i > 0)
+    });
+
+    if is_safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+pub fn render(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .map(|token| token.raw.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(input: &str) -> Vec<String> {
+        tokenize(input)
+            .unwrap()
+            .into_iter()
+            .map(|token| token.value)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_plain_words() {
+        assert_eq!(
+            values("npm  install\treact"),
+            vec!["npm", "install", "react"]
+        );
+        assert!(tokenize("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_quotes() {
+        assert_eq!(
+            values(r#"npm run lint -- --ext ".ts, .tsx""#),
+            vec!["npm", "run", "lint", "--", "--ext", ".ts, .tsx"]
+        );
+        assert_eq!(values("echo 'it''s'"), vec!["echo", "its"]);
+        assert_eq!(values(r#"echo "a \"b\" \n""#), vec!["echo", r#"a "b" \n"#]);
+    }
+
+    #[test]
+    fn test_tokenize_escapes_and_variables() {
+        assert_eq!(values(r"cd my\ app"), vec!["cd", "my app"]);
+        assert_eq!(values("npm i \\\n react"), vec!["npm", "i", "react"]);
+        assert_eq!(
+            values("echo $HOME \"$PATH\""),
+            vec!["echo", "$HOME", "$PATH"]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_keeps_raw_text() {
+        let tokens = tokenize(r#"--ext ".ts, .tsx" my\ app '$HOME'"#).unwrap();
+        let raw: Vec<&str> = tokens.iter().map(|token| token.raw.as_str()).collect();
+        assert_eq!(raw, vec!["--ext", r#"".ts, .tsx""#, r"my\ app", "'$HOME'"]);
+    }
+
+    #[test]
+    fn test_tokenize_errors() {
+        assert!(tokenize("echo 'open").is_err());
+        assert!(tokenize("echo \"open").is_err());
+        assert!(tokenize("echo \\").is_err());
+    }
+
+    #[test]
+    fn test_tokenize_operators() {
+        let tokens = tokenize("(cd app && npm i) || echo fail; a | b 2>&1").unwrap();
+        let operators: Vec<&str> = tokens
+            .iter()
+            .filter(|token| token.is_operator())
+            .map(|token| token.raw.as_str())
+            .collect();
+        assert_eq!(operators, vec!["(", "&&", ")", "||", ";", "|"]);
+
+        // Quoted or escaped operators are plain words
+        assert!(tokenize("echo '&&' \\;")
+            .unwrap()
+            .iter()
+            .all(|token| !token.is_operator()));
+        assert_eq!(values("echo $(npm bin) x"), vec!["echo", "$(npm bin)", "x"]);
+    }
+
+    #[test]
+    fn test_tokenize_redirections() {
+        let tokens = tokenize("npm i > /dev/null 2>&1 <in.txt a>b '>' && c &>> log").unwrap();
+        let raw: Vec<&str> = tokens.iter().map(|token| token.raw.as_str()).collect();
+        assert_eq!(
+            raw,
+            vec![
+                "npm",
+                "i",
+                "> /dev/null",
+                "2>&1",
+                "<in.txt",
+                "a",
+                ">b",
+                "'>'",
+                "&&",
+                "c",
+                "&>> log"
+            ]
+        );
+        let redirections: Vec<&str> = tokens
+            .iter()
+            .filter(|token| token.is_redirection())
+            .map(|token| token.raw.as_str())
+            .collect();
+        assert_eq!(
+            redirections,
+            vec!["> /dev/null", "2>&1", "<in.txt", ">b", "&>> log"]
+        );
+        assert!(!Token::new(">").is_redirection());
+
+        // Placeholders in docs are words
+        assert_eq!(
+            values("npm i <package>@<version> > log"),
+            vec!["npm", "i", "<package>@<version>", ">log"]
+        );
+        assert!(!tokenize("<pkg>").unwrap()[0].is_redirection());
+    }
+
+    #[test]
+    fn test_split_commands() {
+        let input = "cd app && npm install;(yarn build | tee log) \n bun dev";
+        let commands: Vec<&str> = split_commands(input)
+            .unwrap()
+            .into_iter()
+            .map(|range| &input[range])
+            .collect();
+        assert_eq!(
+            commands,
+            vec!["cd app", "npm install", "yarn build", "tee log", "bun dev"]
+        );
+        assert!(split_commands("&& ;").unwrap().is_empty());
+
+        // Comments are left out, quotes and all
+        let input = "npm install # don't && skip\nyarn build";
+        let commands: Vec<&str> = split_commands(input)
+            .unwrap()
+            .into_iter()
+            .map(|range| &input[range])
+            .collect();
+        assert_eq!(commands, vec!["npm install", "yarn build"]);
+    }
+
+    #[test]
+    fn test_strip_prompt() {
+        assert_eq!(strip_prompt("$ npm install"), "npm install");
+        assert_eq!(strip_prompt(">  pnpm dev"), "pnpm dev");
+        assert_eq!(strip_prompt("$npm install"), "$npm install");
+        assert_eq!(strip_prompt("npm install > log"), "npm install > log");
+    }
+
+    #[test]
+    fn test_command_start() {
+        fn command(input: &str) -> &str {
+            &input[command_start(input).unwrap()..]
+        }
+
+        assert_eq!(command("npm run build"), "npm run build");
+        assert_eq!(
+            command("NODE_ENV=production CI='a b' npm run build"),
+            "npm run build"
+        );
+        assert_eq!(command("sudo -E -u root npm i -g pnpm"), "npm i -g pnpm");
+        assert_eq!(command("time -p env -u HOME FOO=1 yarn test"), "yarn test");
+        assert_eq!(command("npx cross-env FOO=1 npm test"), "npm test");
+        assert_eq!(
+            command("npx -y dotenv -e .env.local -- pnpm dev"),
+            "pnpm dev"
+        );
+        assert_eq!(command("sudo -- npm ci"), "npm ci");
+
+        // Not wrappers
+        assert_eq!(command("npx prisma generate"), "npx prisma generate");
+        assert_eq!(command("dotenv pnpm dev"), "dotenv pnpm dev");
+        assert_eq!(command("'FOO'=1 npm test"), "'FOO'=1 npm test");
+        assert_eq!(command("FOO=1"), "");
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("react"), "react");
+        assert_eq!(quote("@types/node@^20.1.0"), "@types/node@^20.1.0");
+        assert_eq!(quote("react@~18"), "react@~18");
+        assert_eq!(quote("~/lib"), "'~/lib'");
+        assert_eq!(quote(".ts, .tsx"), "'.ts, .tsx'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(quote("$HOME"), "'$HOME'");
+        assert_eq!(quote(""), "''");
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        let input = r#"npm run lint -- --ext ".ts, .tsx" my\ app $VAR"#;
+        assert_eq!(render(&tokenize(input).unwrap()), input);
+        assert_eq!(render(&[Token::new("run"), Token::new("a b")]), "run 'a b'");
+    }
+}
@@ -0,0 +1,541 @@
+// Multi-line clipboard content, like a fenced code block copied from a README.
+//
+// Every line is translated on its own. Markdown fences, `#` comments and blank lines are
+// kept as they are, and a command continued over several lines with trailing backslashes
+// keeps its line breaks, as long as the arguments they were in front of survive the
+// translation. Outside code blocks, lines of prose only have their inline code spans
+// translated, as in "Run `npm install zod` and then `npm run dev`." Copied text is only
+// taken as bare commands when every line outside code blocks looks like one.
+
+use std::ops::Range;
+
+use crate::shell;
+
+// Translates the command lines of `text` with `translate_line`, which gets one line (its
+// continuations joined with spaces) and returns its translation, or None to keep the line
+// as it is. Returns None if no line changed.
+pub fn translate_lines(
+    text: &str,
+    translate_line: impl FnMut(&str) -> Option<String>,
+) -> Option<String> {
+    translate_snippet(text, is_all_commands(text), translate_line)
+}
+
+// Like `translate_lines`, for a whole Markdown or text document, where only code blocks
+// and inline code spans hold commands and every other line is prose
+pub fn translate_document(
+    text: &str,
+    translate_line: impl FnMut(&str) -> Option<String>,
+) -> Option<String> {
+    translate_snippet(text, false, translate_line)
+}
+
+// `bare_commands` tells whether lines outside code blocks without inline code are commands
+fn translate_snippet(
+    text: &str,
+    bare_commands: bool,
+    mut translate_line: impl FnMut(&str) -> Option<String>,
+) -> Option<String> {
+    let mut translated = String::new();
+    let mut changed = false;
+    // The marker of the fence that opened the code block the line is in
+    let mut open_fence = None;
+    let mut lines = text.split_inclusive('\n');
+
+    while let Some(first) = lines.next() {
+        // Gather the lines of a command continued with trailing backslashes. In prose, a
+        // trailing backslash is a Markdown line break instead.
+        let mut line = first.to_string();
+        while (open_fence.is_some() || bare_commands) && is_continued(&line) {
+            match lines.next() {
+                Some(next) => line.push_str(next),
+                None => break,
+            }
+        }
+
+        let content = line.trim_end_matches(['\n', '\r']);
+        let ending = &line[content.len()..];
+        let trimmed = content.trim_start();
+        if update_fence(&mut open_fence, trimmed) {
+            translated.push_str(&line);
+            continue;
+        }
+        if trimmed.is_empty() {
+            translated.push_str(&line);
+            continue;
+        }
+
+        // Prose, like a Markdown heading, only has its inline code translated
+        if open_fence.is_none() && (!bare_commands || !inline_code_spans(content).is_empty()) {
+            match translate_inline_code(content, &mut translate_line) {
+                Some(translated_line) => {
+                    translated.push_str(&translated_line);
+                    translated.push_str(ending);
+                    changed = true;
+                }
+                None => translated.push_str(&line),
+            }
+            continue;
+        }
+        if trimmed.starts_with('#') {
+            translated.push_str(&line);
+            continue;
+        }
+
+        let (joined, breaks) = join_continuations(content);
+        match translate_line(&joined) {
+            Some(translated_line) if translated_line != joined => {
+                translated.push_str(&restore_continuations(&joined, &translated_line, &breaks));
+                translated.push_str(ending);
+                changed = true;
+            }
+            _ => translated.push_str(&line),
+        }
+    }
+
+    changed.then_some(translated)
+}
+
+// Translates the commands in the inline code spans of a line of prose, leaving the text
+// around them as it is. Returns None if no command changed.
+fn translate_inline_code(
+    line: &str,
+    translate_line: &mut impl FnMut(&str) -> Option<String>,
+) -> Option<String> {
+    let mut translated = String::new();
+    let mut last_end = 0;
+    let mut changed = false;
+
+    for span in inline_code_spans(line) {
+        translated.push_str(&line[last_end..span.start]);
+        // The text of a <code> tag is HTML, with `<` written as `&lt;`
+        let is_html = line[..span.start].ends_with('>');
+        let code = match is_html {
+            true => decode_entities(&line[span.clone()]),
+            false => line[span.clone()].to_string(),
+        };
+        match translate_line(&code) {
+            Some(translated_code) if translated_code != code => {
+                match is_html {
+                    true => translated.push_str(&encode_entities(&translated_code)),
+                    false => translated.push_str(&translated_code),
+                }
+                changed = true;
+            }
+            _ => translated.push_str(&line[span.clone()]),
+        }
+        last_end = span.end;
+    }
+    translated.push_str(&line[last_end..]);
+
+    changed.then_some(translated)
+}
+
+// Decodes the HTML entities a command in a <code> tag is likely to have
+fn decode_entities(html: &str) -> String {
+    html.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+// Escapes the characters that HTML text can't have as they are
+fn encode_entities(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// The byte ranges of what's inside the inline code spans of a line: Markdown's
+// `backticks` (closed by a run of as many backticks as opened them) and HTML's
+// <code>tags</code>
+fn inline_code_spans(line: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut i = 0;
+
+    while let Some(offset) = line[i..].find(['`', '<']) {
+        let start = i + offset;
+        let rest = &line[start..];
+
+        if rest.starts_with('`') {
+            let ticks = backtick_run(rest);
+            let content_start = start + ticks;
+            match find_backtick_run(line, content_start, ticks) {
+                Some(end) => {
+                    spans.push(content_start..end);
+                    i = end + ticks;
+                }
+                None => i = content_start,
+            }
+        } else if let Some(tag) = rest
+            .strip_prefix("<code")
+            .filter(|tag| tag.starts_with(['>', ' ']))
+        {
+            let Some(tag_end) = tag.find('>') else {
+                break;
+            };
+            let content_start = start + "<code".len() + tag_end + 1;
+            match line[content_start..].find("</code>") {
+                Some(length) => {
+                    spans.push(content_start..content_start + length);
+                    i = content_start + length + "</code>".len();
+                }
+                None => i = content_start,
+            }
+        } else {
+            i = start + 1;
+        }
+    }
+
+    spans
+}
+
+// The number of backticks a text starts with
+fn backtick_run(text: &str) -> usize {
+    text.len() - text.trim_start_matches('`').len()
+}
+
+// Where the next run of exactly `ticks` backticks at or after `from` starts
+fn find_backtick_run(line: &str, from: usize, ticks: usize) -> Option<usize> {
+    let mut i = from;
+    while let Some(offset) = line[i..].find('`') {
+        let start = i + offset;
+        let run = backtick_run(&line[start..]);
+        if run == ticks {
+            return Some(start);
+        }
+        i = start + run;
+    }
+    None
+}
+
+// Whether every line of `text` outside code blocks looks like a shell command, rather than
+// prose like "To get started:\nnpm install the dependencies, then npm run dev."
+fn is_all_commands(text: &str) -> bool {
+    let mut open_fence = None;
+    text.lines().all(|line| {
+        let trimmed = line.trim();
+        if update_fence(&mut open_fence, trimmed) {
+            return true;
+        }
+        open_fence.is_some()
+            || trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || looks_like_command(without_continuation(trimmed))
+    })
+}
+
+// A line without the backslash continuing it on the next one
+fn without_continuation(line: &str) -> &str {
+    let backslashes = line.len() - line.trim_end_matches('\\').len();
+    if backslashes % 2 == 1 {
+        &line[..line.len() - 1]
+    } else {
+        line
+    }
+}
+
+// A sentence starts with a capital letter or has punctuation at the end of its words, a
+// command has neither outside its quotes
+fn looks_like_command(line: &str) -> bool {
+    let Ok(tokens) = shell::tokenize(shell::strip_prompt(line)) else {
+        return false;
+    };
+    let capitalized = tokens
+        .first()
+        .is_some_and(|first| first.raw.starts_with(char::is_uppercase) && !first.raw.contains('='));
+    !capitalized && !tokens.iter().any(|token| ends_sentence(&token.raw))
+}
+
+// Whether a word ends like a clause of a sentence, as in "dependencies," or "it!". Paths
+// like `.` or `../app/.` don't.
+fn ends_sentence(word: &str) -> bool {
+    let mut chars = word.chars().rev();
+    match chars.next() {
+        Some(',' | '!' | '?' | ':') => true,
+        Some('.') => chars.next().is_some_and(char::is_alphabetic),
+        _ => false,
+    }
+}
+
+// The marker of a Markdown code fence like ```` ```bash ```` or `~~~`: its character and
+// length
+fn fence(line: &str) -> Option<(char, usize)> {
+    let marker = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = line.len() - line.trim_start_matches(marker).len();
+    (length >= 3).then_some((marker, length))
+}
+
+// Opens a code block on a fence, or closes the open one on a fence that matches it: the
+// same character, at least as long and without an info string. Other fences inside a code
+// block, like ```` ``` ```` in a `~~~` block, are part of its content. Returns whether the
+// line opened or closed a code block.
+fn update_fence(open_fence: &mut Option<(char, usize)>, line: &str) -> bool {
+    let Some((marker, length)) = fence(line) else {
+        return false;
+    };
+    match *open_fence {
+        None => {
+            *open_fence = Some((marker, length));
+            true
+        }
+        Some((open_marker, open_length))
+            if marker == open_marker
+                && length >= open_length
+                && line[length..].trim().is_empty() =>
+        {
+            *open_fence = None;
+            true
+        }
+        Some(_) => false,
+    }
+}
+
+// Whether a line ends with a backslash that continues the command on the next line
+fn is_continued(line: &str) -> bool {
+    let content = line.trim_end_matches(['\n', '\r']);
+    let backslashes = content.len() - content.trim_end_matches('\\').len();
+    line.ends_with('\n') && backslashes % 2 == 1
+}
+
+// Joins the lines of a continued command with spaces, returning the joined command and
+// where each line break was: the position in the joined command the next line starts at,
+// and the text the break replaced (` \` up to the next line's indentation)
+fn join_continuations(command: &str) -> (String, Vec<(usize, String)>) {
+    let mut joined = String::new();
+    let mut breaks = Vec::new();
+    let mut pending_break: Option<String> = None;
+
+    for part in command.split_inclusive('\n') {
+        let mut part = part;
+        if let Some(mut separator) = pending_break.take() {
+            let rest = part.trim_start_matches([' ', '\t']);
+            separator.push_str(&part[..part.len() - rest.len()]);
+            part = rest;
+            joined.push(' ');
+            breaks.push((joined.len(), separator));
+        }
+
+        if is_continued(part) {
+            let before_backslash = part.trim_end_matches(['\n', '\r']);
+            let before_backslash = &before_backslash[..before_backslash.len() - 1];
+            let kept = before_backslash.trim_end_matches([' ', '\t']);
+            joined.push_str(kept);
+            pending_break = Some(part[kept.len()..].to_string());
+        } else {
+            joined.push_str(part);
+        }
+    }
+
+    (joined, breaks)
+}
+
+// Puts the line breaks of a continued command back into its translation, each in front of
+// the argument it was in front of before. Breaks whose argument was dropped are left out.
+fn restore_continuations(joined: &str, translated: &str, breaks: &[(usize, String)]) -> String {
+    let (Ok(before), Ok(after)) = (shell::scan(joined), shell::scan(translated)) else {
+        return translated.to_string();
+    };
+
+    let mut restored = String::new();
+    let mut last_end = 0;
+    for (position, separator) in breaks {
+        let Some(index) = before.iter().position(|(_, span)| span.start == *position) else {
+            continue;
+        };
+        // Find the same argument in the translation, counting earlier arguments spelled
+        // the same way
+        let raw = &before[index].0.raw;
+        let occurrence = before[..index]
+            .iter()
+            .filter(|(token, _)| token.raw == *raw)
+            .count();
+        let Some(found) = (1..after.len())
+            .filter(|&i| after[i].0.raw == *raw)
+            .nth(occurrence)
+        else {
+            continue;
+        };
+
+        let previous_end = after[found - 1].1.end;
+        if previous_end < last_end {
+            continue;
+        }
+        restored.push_str(&translated[last_end..previous_end]);
+        restored.push_str(separator);
+        last_end = after[found].1.start;
+    }
+    restored.push_str(&translated[last_end..]);
+
+    restored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Stands in for the real translation: npm commands become pnpm commands
+    fn to_pnpm(line: &str) -> Option<String> {
+        let start = line.len() - line.trim_start().len();
+        let command = line[start..].strip_prefix("npm install ")?;
+        Some(format!("{}pnpm add {}", &line[..start], command))
+    }
+
+    #[test]
+    fn test_translate_lines() {
+        let block = "```bash\n# Install the dependencies\nnpm install react\n\n  npm install zod\r\necho done\n```\n";
+        assert_eq!(
+            translate_lines(block, to_pnpm),
+            Some("```bash\n# Install the dependencies\npnpm add react\n\n  pnpm add zod\r\necho done\n```\n".to_string())
+        );
+
+        assert_eq!(translate_lines("echo one\necho two", to_pnpm), None);
+        assert_eq!(
+            translate_lines("npm install react", to_pnpm),
+            Some("pnpm add react".to_string())
+        );
+    }
+
+    #[test]
+    fn test_inline_code() {
+        assert_eq!(
+            translate_lines(
+                "Run `npm install zod` and then `npm run dev`.\nOr <code>npm install react</code>!",
+                to_pnpm
+            ),
+            Some(
+                "Run `pnpm add zod` and then `npm run dev`.\nOr <code>pnpm add react</code>!"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            translate_lines("Use ``npm install `x` `` or `npm install", to_pnpm),
+            Some("Use ``pnpm add `x` `` or `npm install".to_string())
+        );
+        assert_eq!(translate_lines("Use `npm install", to_pnpm), None);
+
+        // HTML entities are decoded for the translation and encoded again
+        assert_eq!(
+            translate_lines(
+                "Run <code>npm install &lt;pkg&gt; &amp;&amp; ls</code>",
+                to_pnpm
+            ),
+            Some("Run <code>pnpm add &lt;pkg&gt; &amp;&amp; ls</code>".to_string())
+        );
+        assert_eq!(
+            translate_lines("Run `npm install &lt;pkg&gt;`", to_pnpm),
+            Some("Run `pnpm add &lt;pkg&gt;`".to_string())
+        );
+
+        // Backticks in a code block are command substitutions
+        assert_eq!(
+            translate_lines("```\nnpm install `cat deps`\n```", to_pnpm),
+            Some("```\npnpm add `cat deps`\n```".to_string())
+        );
+        assert_eq!(translate_lines("npm install `cat deps`", to_pnpm), None);
+    }
+
+    #[test]
+    fn test_inline_code_spans() {
+        let spans = |line: &str| -> Vec<String> {
+            inline_code_spans(line)
+                .into_iter()
+                .map(|span| line[span].to_string())
+                .collect()
+        };
+
+        assert_eq!(spans("a `b` c ``d ` e`` `f"), vec!["b", "d ` e"]);
+        assert_eq!(
+            spans("<code class=\"sh\">npm i</code> <coder>x</coder> <code>y"),
+            vec!["npm i"]
+        );
+    }
+
+    #[test]
+    fn test_copied_prose() {
+        // Text is prose unless every line outside code blocks looks like a command
+        assert_eq!(
+            translate_lines(
+                "To get started:\nnpm install the dependencies, then npm run dev.",
+                to_pnpm
+            ),
+            None
+        );
+        assert_eq!(
+            translate_lines(
+                "npm install is all it takes. Then `npm install zod`",
+                to_pnpm
+            ),
+            Some("npm install is all it takes. Then `pnpm add zod`".to_string())
+        );
+        assert_eq!(
+            translate_lines("Install it:\n```\nnpm install react\n```", to_pnpm),
+            Some("Install it:\n```\npnpm add react\n```".to_string())
+        );
+        assert_eq!(
+            translate_lines(
+                "# Set up\nnpm install react\nnpx prettier --write .\n",
+                to_pnpm
+            ),
+            Some("# Set up\npnpm add react\nnpx prettier --write .\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_continuations() {
+        let command = "npm install \\\n    react \\\n    react-dom\nnpm test";
+        assert_eq!(
+            translate_lines(command, to_pnpm),
+            Some("pnpm add \\\n    react \\\n    react-dom\nnpm test".to_string())
+        );
+
+        // A break in front of a dropped argument goes away with it
+        let dropped = |line: &str| Some(line.replace(" --legacy-peer-deps", ""));
+        assert_eq!(
+            translate_lines("npm install \\\n  --legacy-peer-deps \\\n  react", dropped),
+            Some("npm install \\\n  react".to_string())
+        );
+
+        // An escaped backslash doesn't continue the line
+        assert_eq!(
+            translate_lines("npm install a\\\\\nnpm install b", to_pnpm),
+            Some("pnpm add a\\\\\npnpm add b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_translate_document() {
+        let document = "# Setup\n\nnpm install is all it takes:\\\n```sh\nnpm install \\\n  react\n```\nOr run `npm install zod`.\n";
+        assert_eq!(
+            translate_document(document, to_pnpm),
+            Some("# Setup\n\nnpm install is all it takes:\\\n```sh\npnpm add \\\n  react\n```\nOr run `pnpm add zod`.\n".to_string())
+        );
+        assert_eq!(translate_document("npm install react\n", to_pnpm), None);
+
+        // Comments are only skipped in code blocks, a heading is prose
+        assert_eq!(
+            translate_document(
+                "## Add `npm install zod`\n```sh\n# npm install zod\n```\n",
+                to_pnpm
+            ),
+            Some("## Add `pnpm add zod`\n```sh\n# npm install zod\n```\n".to_string())
+        );
+
+        // Only a fence like the opening one closes a code block
+        assert_eq!(
+            translate_document(
+                "~~~md\n```sh\nnpm install react\n```\n~~~\nnpm install is prose\n",
+                to_pnpm
+            ),
+            Some("~~~md\n```sh\npnpm add react\n```\n~~~\nnpm install is prose\n".to_string())
+        );
+        assert_eq!(
+            translate_document(
+                "~~~~\nnpm install a\n~~~\nnpm install b\n~~~~\nnpm install c\n",
+                to_pnpm
+            ),
+            Some("~~~~\npnpm add a\n~~~\npnpm add b\n~~~~\nnpm install c\n".to_string())
+        );
+    }
+}
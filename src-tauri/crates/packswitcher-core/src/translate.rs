@@ -0,0 +1,1475 @@
+// Translation of copied text: code blocks, prose with inline code and compound shell lines
+// are split into simple commands, which are translated with the rules or by parsing and
+// rendering them for the target package manager.
+
+use crate::package_manager::{PackageManager, YarnDialect};
+use crate::rules::{self, Rule};
+use crate::{parse, render, shell, snippet};
+
+/// The outcome of translating a text with [`translate`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TranslationResult {
+    /// The text that was translated
+    pub original: String,
+    /// The translated text, with everything that isn't a package manager command kept as
+    /// it was
+    pub translated: String,
+    /// The package managers the translated commands were written for
+    pub from: Vec<PackageManager>,
+    /// The package manager the commands were translated for
+    pub to: PackageManager,
+    /// How each command was translated: the operation it was understood as, or the rule
+    /// that matched
+    pub rules: Vec<String>,
+    /// Everything that could not be translated exactly, like dropped flags
+    pub warnings: Vec<String>,
+}
+
+impl TranslationResult {
+    // Adds how a part of the text was translated, keeping this result's text
+    fn merge(&mut self, part: TranslationResult) {
+        for pm in part.from {
+            if !self.from.contains(&pm) {
+                self.from.push(pm);
+            }
+        }
+        self.rules.extend(part.rules);
+        self.warnings.extend(part.warnings);
+    }
+}
+
+/// Translates the package manager commands in `text` for `to_pm`.
+///
+/// `text` can be a single command, a compound shell line like `npm ci && npm test`, a
+/// Markdown code block or prose with inline code. Shell prompts, environment assignments
+/// and wrappers like `sudo` are kept, as is everything that isn't a package manager
+/// command. `yarn_dialect` picks the Yarn version to translate for when `to_pm` is Yarn,
+/// and `user_rules` (see [`load_rules`](crate::load_rules)) are tried before the built-in
+/// translation.
+///
+/// Returns None when there was nothing to translate.
+///
+/// ```
+/// use packswitcher_core::{translate, PackageManager, YarnDialect};
+///
+/// let result = translate("npm i -D vitest", PackageManager::Pnpm, YarnDialect::Berry, &[])
+///     .unwrap();
+/// assert_eq!(result.translated, "pnpm add -D vitest");
+/// assert_eq!(result.from, vec![PackageManager::Npm]);
+/// ```
+pub fn translate(
+    text: &str,
+    to_pm: PackageManager,
+    yarn_dialect: YarnDialect,
+    user_rules: &[Rule],
+) -> Option<TranslationResult> {
+    translate_text(text, false, to_pm, yarn_dialect, user_rules)
+}
+
+/// Translates the package manager commands in a Markdown or text document for `to_pm`,
+/// like [`translate`] but only in fenced code blocks and inline code. Every other line is
+/// prose and kept as it is, even when it starts like a command. Prompts like `$ ` stay in
+/// front of the translated commands.
+///
+/// Returns None when there was nothing to translate.
+///
+/// ```
+/// use packswitcher_core::{translate_document, PackageManager, YarnDialect};
+///
+/// let document = "npm install is all it takes:\n\n```sh\nnpm install react\n```\n";
+/// let result = translate_document(document, PackageManager::Bun, YarnDialect::Berry, &[])
+///     .unwrap();
+/// assert_eq!(
+///     result.translated,
+///     "npm install is all it takes:\n\n```sh\nbun add react\n```\n"
+/// );
+/// ```
+pub fn translate_document(
+    text: &str,
+    to_pm: PackageManager,
+    yarn_dialect: YarnDialect,
+    user_rules: &[Rule],
+) -> Option<TranslationResult> {
+    translate_text(text, true, to_pm, yarn_dialect, user_rules)
+}
+
+fn translate_text(
+    text: &str,
+    document: bool,
+    to_pm: PackageManager,
+    yarn_dialect: YarnDialect,
+    user_rules: &[Rule],
+) -> Option<TranslationResult> {
+    let mut result = TranslationResult {
+        original: text.to_string(),
+        translated: String::new(),
+        from: Vec::new(),
+        to: to_pm,
+        rules: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    // Snippets like a copied code block are translated line by line
+    let translate_line = |line: &str| {
+        let line_result = translate_line(line, document, to_pm, yarn_dialect, user_rules)?;
+        let translated = line_result.translated.clone();
+        result.merge(line_result);
+        Some(translated)
+    };
+    let translated = if document {
+        snippet::translate_document(text, translate_line)
+    } else {
+        snippet::translate_lines(text, translate_line)
+    }?;
+    result.translated = translated;
+
+    Some(result)
+}
+
+/// Returns the package manager a command is written for, like npm for `npx tsc` or
+/// `$ sudo npm i -g yarn`. For a compound line, the first package manager command decides.
+///
+/// ```
+/// use packswitcher_core::{detect_package_manager, PackageManager};
+///
+/// assert_eq!(detect_package_manager("cd app && yarn build"), Some(PackageManager::Yarn));
+/// assert_eq!(detect_package_manager("make build"), None);
+/// ```
+pub fn detect_package_manager(command: &str) -> Option<PackageManager> {
+    let command = shell::strip_prompt(command.trim());
+    shell::split_commands(command)
+        .ok()?
+        .into_iter()
+        .find_map(|segment| {
+            let segment = &command[segment];
+            let start = shell::command_start(segment).ok()?;
+            let simple_command = &segment[start..];
+            match parse::parse(simple_command) {
+                Some(parsed) => Some(parsed.pm),
+                None => {
+                    let program = shell::tokenize(simple_command).ok()?.into_iter().next()?;
+                    program.value.parse().ok()
+                }
+            }
+        })
+}
+
+// Translates a line of shell. A prompt in front of it is removed unless `keep_prompt` is
+// set, and each simple command of a compound line (`a && b`, `a | b`, ...) is translated on
+// its own, leaving operators, subshells and everything in between exactly as written.
+fn translate_line(
+    line: &str,
+    keep_prompt: bool,
+    to_pm: PackageManager,
+    yarn_dialect: YarnDialect,
+    user_rules: &[Rule],
+) -> Option<TranslationResult> {
+    let prompted = line.trim_start();
+    let command = shell::strip_prompt(prompted);
+    let kept = if keep_prompt {
+        &line[..line.len() - command.len()]
+    } else {
+        &line[..line.len() - prompted.len()]
+    };
+    let segments = shell::split_commands(command).ok()?;
+
+    let mut result = TranslationResult {
+        original: line.to_string(),
+        translated: kept.to_string(),
+        from: Vec::new(),
+        to: to_pm,
+        rules: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let mut last_end = 0;
+    let mut changed = false;
+
+    for mut segment in segments {
+        // Keep environment assignments and wrappers like `sudo` and translate what they run
+        segment.start += shell::command_start(&command[segment.clone()]).ok()?;
+
+        result
+            .translated
+            .push_str(&command[last_end..segment.start]);
+        let original = &command[segment.clone()];
+        match translate_simple_command(original, to_pm, yarn_dialect, user_rules) {
+            Some(segment_result) if segment_result.translated != original => {
+                result.translated.push_str(&segment_result.translated);
+                result.merge(segment_result);
+                changed = true;
+            }
+            _ => result.translated.push_str(original),
+        }
+        last_end = segment.end;
+    }
+    result.translated.push_str(&command[last_end..]);
+
+    changed.then_some(result)
+}
+
+fn translate_simple_command(
+    command: &str,
+    to_pm: PackageManager,
+    yarn_dialect: YarnDialect,
+    user_rules: &[Rule],
+) -> Option<TranslationResult> {
+    let mut warnings = Vec::new();
+    if let Some(translation) = rules::translate(user_rules, command, to_pm, &mut warnings) {
+        return Some(TranslationResult {
+            original: command.to_string(),
+            translated: translation.translated,
+            from: vec![translation.from],
+            to: to_pm,
+            rules: vec![translation.rule],
+            warnings,
+        });
+    }
+
+    let parsed = parse::parse(command)?;
+    if parsed.pm == to_pm {
+        return None;
+    }
+
+    let translated = render::render(&parsed, to_pm, yarn_dialect, &mut warnings)?;
+    Some(TranslationResult {
+        original: command.to_string(),
+        translated,
+        from: vec![parsed.pm],
+        to: to_pm,
+        rules: vec![parsed.operation.name().to_string()],
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_manager::PackageManager::{Bun, Deno, Npm, Pnpm, Yarn};
+
+    // The translated text of a simple command
+    fn translate_text(
+        command: &str,
+        to_pm: PackageManager,
+        yarn_dialect: YarnDialect,
+    ) -> Option<String> {
+        translate_simple_command(command, to_pm, yarn_dialect, &[]).map(|result| result.translated)
+    }
+
+    // Translates a command without checking which package manager it was written for
+    fn translate(
+        command: &str,
+        to_pm: PackageManager,
+        yarn_dialect: YarnDialect,
+    ) -> Option<String> {
+        let parsed = parse::parse(command)?;
+        render::render(&parsed, to_pm, yarn_dialect, &mut Vec::new())
+    }
+
+    #[test]
+    fn test_pnpm_dlx_conversion() {
+        // Test pnpm dlx -> other package managers
+        assert_eq!(
+            translate_text("pnpm dlx prisma generate", Npm, YarnDialect::Berry),
+            Some("npx prisma generate".to_string())
+        );
+        assert_eq!(
+            translate_text("pnpm dlx prisma generate", Yarn, YarnDialect::Berry),
+            Some("yarn dlx prisma generate".to_string())
+        );
+        assert_eq!(
+            translate_text("pnpm dlx prisma generate", Bun, YarnDialect::Berry),
+            Some("bunx prisma generate".to_string())
+        );
+
+        // Test that pnpm dlx stays unchanged when preferred PM is pnpm
+        assert_eq!(
+            translate_text("pnpx prisma generate", Pnpm, YarnDialect::Berry),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pnpx_conversion() {
+        // Test pnpx -> other package managers
+        assert_eq!(
+            translate_text("pnpx prisma generate", Npm, YarnDialect::Berry),
+            Some("npx prisma generate".to_string())
+        );
+        assert_eq!(
+            translate_text("pnpx prisma generate", Yarn, YarnDialect::Berry),
+            Some("yarn dlx prisma generate".to_string())
+        );
+        assert_eq!(
+            translate_text("pnpx prisma generate", Bun, YarnDialect::Berry),
+            Some("bunx prisma generate".to_string())
+        );
+
+        // Test that pnpx stays unchanged when preferred PM is pnpm
+        assert_eq!(
+            translate_text("pnpx prisma generate", Pnpm, YarnDialect::Berry),
+            None
+        );
+    }
+
+    #[test]
+    fn test_other_runners_to_pnpm() {
+        // Test that other runners convert to pnpm dlx (not pnpx)
+        assert_eq!(
+            translate_text("npx prisma generate", Pnpm, YarnDialect::Berry),
+            Some("pnpx prisma generate".to_string())
+        );
+        assert_eq!(
+            translate_text("bunx prisma generate", Pnpm, YarnDialect::Berry),
+            Some("pnpx prisma generate".to_string())
+        );
+        assert_eq!(
+            translate_text("yarn dlx prisma generate", Pnpm, YarnDialect::Berry),
+            Some("pnpx prisma generate".to_string())
+        );
+    }
+
+    // New comprehensive tests for package manager translations
+    #[test]
+    fn test_npm_install_translations() {
+        // npm install -> add for other package managers
+        assert_eq!(
+            translate("npm install react", Pnpm, YarnDialect::Classic),
+            Some("pnpm add react".to_string())
+        );
+        assert_eq!(
+            translate("npm install react", Yarn, YarnDialect::Classic),
+            Some("yarn add react".to_string())
+        );
+        assert_eq!(
+            translate("npm install react", Bun, YarnDialect::Classic),
+            Some("bun add react".to_string())
+        );
+
+        // npm i (shorthand) -> add
+        assert_eq!(
+            translate("npm i react", Pnpm, YarnDialect::Classic),
+            Some("pnpm add react".to_string())
+        );
+    }
+
+    #[test]
+    fn test_npm_uninstall_translations() {
+        // npm uninstall -> remove for other package managers
+        assert_eq!(
+            translate("npm uninstall react", Pnpm, YarnDialect::Classic),
+            Some("pnpm remove react".to_string())
+        );
+        assert_eq!(
+            translate("npm uninstall react", Yarn, YarnDialect::Classic),
+            Some("yarn remove react".to_string())
+        );
+        assert_eq!(
+            translate("npm uninstall react", Bun, YarnDialect::Classic),
+            Some("bun remove react".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pnpm_add_translations() {
+        // pnpm add -> install for npm
+        assert_eq!(
+            translate("pnpm add react", Npm, YarnDialect::Classic),
+            Some("npm install react".to_string())
+        );
+
+        // pnpm add -> add for other package managers
+        assert_eq!(
+            translate("pnpm add react", Yarn, YarnDialect::Classic),
+            Some("yarn add react".to_string())
+        );
+        assert_eq!(
+            translate("pnpm add react", Bun, YarnDialect::Classic),
+            Some("bun add react".to_string())
+        );
+    }
+
+    #[test]
+    fn test_yarn_add_translations() {
+        // yarn add -> install for npm
+        assert_eq!(
+            translate("yarn add react", Npm, YarnDialect::Classic),
+            Some("npm install react".to_string())
+        );
+
+        // yarn add -> add for other package managers
+        assert_eq!(
+            translate("yarn add react", Pnpm, YarnDialect::Classic),
+            Some("pnpm add react".to_string())
+        );
+        assert_eq!(
+            translate("yarn add react", Bun, YarnDialect::Classic),
+            Some("bun add react".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bun_add_translations() {
+        // bun add -> install for npm
+        assert_eq!(
+            translate("bun add react", Npm, YarnDialect::Classic),
+            Some("npm install react".to_string())
+        );
+
+        // bun add -> add for other package managers
+        assert_eq!(
+            translate("bun add react", Pnpm, YarnDialect::Classic),
+            Some("pnpm add react".to_string())
+        );
+        assert_eq!(
+            translate("bun add react", Yarn, YarnDialect::Classic),
+            Some("yarn add react".to_string())
+        );
+    }
+
+    #[test]
+    fn test_global_install_translations() {
+        // npm install -g -> various global installs
+        assert_eq!(
+            translate("npm install -g typescript", Pnpm, YarnDialect::Classic),
+            Some("pnpm add -g typescript".to_string())
+        );
+        assert_eq!(
+            translate("npm install -g typescript", Yarn, YarnDialect::Classic),
+            Some("yarn global add typescript".to_string())
+        );
+        assert_eq!(
+            translate("npm install -g typescript", Bun, YarnDialect::Classic),
+            Some("bun add -g typescript".to_string())
+        );
+
+        // Test --global flag
+        assert_eq!(
+            translate(
+                "npm install --global typescript",
+                Pnpm,
+                YarnDialect::Classic
+            ),
+            Some("pnpm add -g typescript".to_string())
+        );
+
+        // Test global flag with no package name
+        assert_eq!(
+            translate("npm install -g", Pnpm, YarnDialect::Classic),
+            Some("pnpm add -g".to_string())
+        );
+
+        // Package names containing "-g" are not global installs
+        assert_eq!(
+            translate(
+                "npm install -g vite-plugin-graphql-gen",
+                Bun,
+                YarnDialect::Classic
+            ),
+            Some("bun add -g vite-plugin-graphql-gen".to_string())
+        );
+        assert_eq!(
+            translate("npm i @scope/pkg-gen", Yarn, YarnDialect::Classic),
+            Some("yarn add @scope/pkg-gen".to_string())
+        );
+
+        // Combined short flags
+        assert_eq!(
+            translate("npm i -gE typescript", Yarn, YarnDialect::Classic),
+            Some("yarn global add -E typescript".to_string())
+        );
+        assert_eq!(
+            translate("npm i -DE vitest", Yarn, YarnDialect::Classic),
+            Some("yarn add -D -E vitest".to_string())
+        );
+
+        // Global removals and yarn's global syntax
+        assert_eq!(
+            translate("npm uninstall -g typescript", Yarn, YarnDialect::Classic),
+            Some("yarn global remove typescript".to_string())
+        );
+        assert_eq!(
+            translate("yarn global add typescript", Npm, YarnDialect::Classic),
+            Some("npm install -g typescript".to_string())
+        );
+        assert_eq!(
+            translate("yarn global remove typescript", Bun, YarnDialect::Classic),
+            Some("bun remove -g typescript".to_string())
+        );
+    }
+
+    #[test]
+    fn test_script_running_translations() {
+        // Regular script commands should add "run" for non-yarn package managers
+        assert_eq!(
+            translate("yarn build", Npm, YarnDialect::Classic),
+            Some("npm run build".to_string())
+        );
+        assert_eq!(
+            translate("yarn test", Pnpm, YarnDialect::Classic),
+            Some("pnpm run test".to_string())
+        );
+        assert_eq!(
+            translate("pnpm --silent build", Npm, YarnDialect::Classic),
+            Some("npm run --silent build".to_string())
+        );
+        assert_eq!(
+            translate("yarn dev", Bun, YarnDialect::Classic),
+            Some("bun run dev".to_string())
+        );
+
+        // yarn -> yarn should keep the same format
+        assert_eq!(
+            translate("yarn build", Yarn, YarnDialect::Classic),
+            Some("yarn build".to_string())
+        );
+
+        // pnpm and bun run scripts by name too
+        assert_eq!(
+            translate("pnpm dev", Npm, YarnDialect::Classic),
+            Some("npm run dev".to_string())
+        );
+        assert_eq!(
+            translate("pnpm dev --port 3000", Yarn, YarnDialect::Classic),
+            Some("yarn dev --port 3000".to_string())
+        );
+        assert_eq!(
+            translate("bun dev", Npm, YarnDialect::Classic),
+            Some("npm run dev".to_string())
+        );
+        assert_eq!(
+            translate("pnpm lint", Deno, YarnDialect::Classic),
+            Some("deno task lint".to_string())
+        );
+
+        // `bun upgrade` upgrades bun itself
+        assert_eq!(translate("bun upgrade", Npm, YarnDialect::Classic), None);
+    }
+
+    #[test]
+    fn test_script_commands_with_run() {
+        // Commands that already have "run" should work correctly
+        assert_eq!(
+            translate("npm run build", Pnpm, YarnDialect::Classic),
+            Some("pnpm run build".to_string())
+        );
+        assert_eq!(
+            translate("pnpm run test", Yarn, YarnDialect::Classic),
+            Some("yarn run test".to_string())
+        );
+    }
+
+    #[test]
+    fn test_script_argument_separator() {
+        let translate = |command: &str, to_pm: PackageManager| {
+            translate_text(command, to_pm, YarnDialect::Classic)
+        };
+
+        // npm needs `--` to forward options, the others pass them on directly
+        assert_eq!(
+            translate("npm run test -- --watch", Pnpm),
+            Some("pnpm run test --watch".to_string())
+        );
+        assert_eq!(
+            translate("npm run test -- --watch", Yarn),
+            Some("yarn run test --watch".to_string())
+        );
+        // `bun test` is bun's own test runner, not the package.json script
+        assert_eq!(
+            translate("npm test -- --watch", Bun),
+            Some("bun run test --watch".to_string())
+        );
+        assert_eq!(translate("npm start", Bun), Some("bun start".to_string()));
+        assert_eq!(translate("bun test --watch", Npm), None);
+        assert_eq!(
+            translate("pnpm run test --watch", Npm),
+            Some("npm run test -- --watch".to_string())
+        );
+        assert_eq!(
+            translate("yarn test --coverage src", Npm),
+            Some("npm run test -- --coverage src".to_string())
+        );
+        assert_eq!(
+            translate("bun run --silent dev --port 3000", Npm),
+            Some("npm run --silent dev -- --port 3000".to_string())
+        );
+
+        // Only the first separator is consumed, later ones belong to the script
+        assert_eq!(
+            translate("npm run lint -- -- --fix", Pnpm),
+            Some("pnpm run lint -- --fix".to_string())
+        );
+
+        // npm reads options in front of `--` as its own, they don't reach the script
+        assert_eq!(
+            translate("npm run build --if-present", Pnpm),
+            Some("pnpm run --if-present build".to_string())
+        );
+        assert_eq!(
+            translate("npm run build -ws --if-present", Bun),
+            Some("bun --filter '*' run --if-present build".to_string())
+        );
+        assert_eq!(
+            translate("npm run build --silent", Bun),
+            Some("bun run --silent build".to_string())
+        );
+        assert_eq!(
+            translate("npm test --silent -- --watch", Yarn),
+            Some("yarn --silent test --watch".to_string())
+        );
+        assert_eq!(
+            translate("npm run build --loglevel warn dist", Pnpm),
+            Some("pnpm run --loglevel warn build dist".to_string())
+        );
+
+        // Scripts without arguments get no separator
+        assert_eq!(
+            translate("yarn build", Npm),
+            Some("npm run build".to_string())
+        );
+
+        // npm workspace flags stay ahead of the separator
+        assert_eq!(
+            translate("yarn workspace web test --watch", Npm),
+            Some("npm run test -w web -- --watch".to_string())
+        );
+    }
+
+    #[test]
+    fn test_deno_translations() {
+        let to_deno = |command: &str| translate_text(command, Deno, YarnDialect::Classic);
+        let from_deno = |command: &str, to_pm: PackageManager| {
+            translate_text(command, to_pm, YarnDialect::Berry)
+        };
+
+        // Packages get an `npm:` specifier, scripts become tasks
+        assert_eq!(
+            to_deno("npm i react"),
+            Some("deno add npm:react".to_string())
+        );
+        assert_eq!(
+            to_deno("yarn add -D @types/node@^20 jsr:@std/path ./lib"),
+            Some("deno add -D npm:@types/node@^20 jsr:@std/path ./lib".to_string())
+        );
+        assert_eq!(
+            to_deno("npm uninstall react"),
+            Some("deno remove react".to_string())
+        );
+        assert_eq!(to_deno("pnpm install"), Some("deno install".to_string()));
+        assert_eq!(to_deno("npm ci"), Some("deno install --frozen".to_string()));
+        assert_eq!(to_deno("npm run dev"), Some("deno task dev".to_string()));
+        assert_eq!(
+            to_deno("npm test -- --watch"),
+            Some("deno task test --watch".to_string())
+        );
+        assert_eq!(to_deno("yarn build"), Some("deno task build".to_string()));
+        assert_eq!(
+            to_deno("pnpm --filter web run dev"),
+            Some("deno task --filter web dev".to_string())
+        );
+        assert_eq!(
+            to_deno("npm update react"),
+            Some("deno outdated --update react".to_string())
+        );
+        assert_eq!(
+            to_deno("npm i -g typescript"),
+            Some("deno install -g -A npm:typescript".to_string())
+        );
+        assert_eq!(to_deno("npm publish"), None);
+
+        // Runners and initializers run npm packages with `deno run`
+        assert_eq!(
+            to_deno("npx cowsay hello"),
+            Some("deno run -A npm:cowsay hello".to_string())
+        );
+        assert_eq!(
+            to_deno("bunx -y cowsay"),
+            Some("deno run -A npm:cowsay".to_string())
+        );
+        assert_eq!(
+            to_deno("npm create vite@latest my-app -- --template react"),
+            Some("deno run -A npm:create-vite@latest my-app --template react".to_string())
+        );
+        assert_eq!(
+            to_deno("npx jsr add -D @std/assert"),
+            Some("deno add -D jsr:@std/assert".to_string())
+        );
+
+        // And back again
+        assert_eq!(
+            from_deno("deno add npm:react", Npm),
+            Some("npm install react".to_string())
+        );
+        assert_eq!(
+            from_deno("deno add --dev npm:vitest", Pnpm),
+            Some("pnpm add -D vitest".to_string())
+        );
+        assert_eq!(
+            from_deno("deno add npm:react jsr:@std/path", Pnpm),
+            Some("pnpm add react && pnpx jsr add @std/path".to_string())
+        );
+        assert_eq!(
+            from_deno("deno add jsr:@std/path", Yarn),
+            Some("yarn dlx jsr add @std/path".to_string())
+        );
+        assert_eq!(
+            from_deno("deno remove npm:react", Bun),
+            Some("bun remove react".to_string())
+        );
+        assert_eq!(
+            from_deno("deno install", Yarn),
+            Some("yarn install".to_string())
+        );
+        assert_eq!(
+            from_deno("deno install --frozen", Pnpm),
+            Some("pnpm install --frozen-lockfile".to_string())
+        );
+        assert_eq!(
+            from_deno("deno install -g -A npm:typescript", Npm),
+            Some("npm install -g typescript".to_string())
+        );
+        assert_eq!(
+            from_deno("deno task dev", Pnpm),
+            Some("pnpm run dev".to_string())
+        );
+        assert_eq!(
+            from_deno("deno task test --watch", Npm),
+            Some("npm run test -- --watch".to_string())
+        );
+        assert_eq!(
+            from_deno("deno task --filter web dev", Pnpm),
+            Some("pnpm --filter web run dev".to_string())
+        );
+        assert_eq!(
+            from_deno("deno outdated --update react", Yarn),
+            Some("yarn up react".to_string())
+        );
+        assert_eq!(
+            from_deno("deno run -A npm:cowsay hello", Bun),
+            Some("bunx cowsay hello".to_string())
+        );
+        assert_eq!(
+            from_deno("deno run -A npm:create-vite my-app", Npm),
+            Some("npm create vite my-app".to_string())
+        );
+
+        // Deno programs have no package manager equivalent
+        assert_eq!(from_deno("deno run -A main.ts", Npm), None);
+        assert_eq!(
+            from_deno("deno install -g jsr:@std/http/file-server", Npm),
+            None
+        );
+        assert_eq!(from_deno("deno fmt", Npm), None);
+    }
+
+    #[test]
+    fn test_translate_simple_command() {
+        // Test the main translation entry point
+        assert_eq!(
+            translate_text("npm install react", Pnpm, YarnDialect::Classic),
+            Some("pnpm add react".to_string())
+        );
+        assert_eq!(
+            translate_text("yarn add typescript", Npm, YarnDialect::Classic),
+            Some("npm install typescript".to_string())
+        );
+
+        // Should return None when source and target are the same
+        assert_eq!(
+            translate_text("npm install react", Npm, YarnDialect::Classic),
+            None
+        );
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        // Bare installs install from the lockfile
+        assert_eq!(
+            translate("npm install", Pnpm, YarnDialect::Classic),
+            Some("pnpm install".to_string())
+        );
+
+        // Single character commands should return None
+        assert_eq!(translate("npm", Pnpm, YarnDialect::Classic), None);
+
+        // Commands with multiple packages
+        assert_eq!(
+            translate("npm install react react-dom", Pnpm, YarnDialect::Classic),
+            Some("pnpm add react react-dom".to_string())
+        );
+
+        // Empty and non-ASCII package names don't panic
+        for command in [
+            "npx \"\"",
+            "npx é",
+            "bunx \"\"",
+            "yarn dlx \"\"",
+            "pnpm dlx é",
+            "deno run npm:",
+            "deno run -A npm:é",
+            "npm create é",
+            "npm init \"\"",
+        ] {
+            for pm in PackageManager::ALL {
+                for dialect in [YarnDialect::Classic, YarnDialect::Berry] {
+                    translate_text(command, pm, dialect);
+                }
+            }
+        }
+        assert_eq!(
+            translate_text("npm create é", Deno, YarnDialect::Classic),
+            Some("deno run -A 'npm:create-é'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_yarn_dialects() {
+        // Runners: `yarn dlx` only exists in Yarn 2+
+        assert_eq!(
+            translate_text("bunx cowsay", Yarn, YarnDialect::Classic),
+            Some("npx cowsay".to_string())
+        );
+
+        // Global installs: `yarn global` only exists in Yarn 1, Yarn 2+ falls back to npm
+        let with_warnings = |command: &str| {
+            translate_simple_command(command, Yarn, YarnDialect::Berry, &[])
+                .map(|result| (result.translated, result.warnings))
+        };
+        let berry_global = vec!["yarn 2+ has no global packages, used npm instead".to_string()];
+        assert_eq!(
+            with_warnings("pnpm add -g typescript"),
+            Some((
+                "npm install -g typescript".to_string(),
+                berry_global.clone()
+            ))
+        );
+        assert_eq!(
+            with_warnings("npm uninstall -g typescript"),
+            Some(("npm uninstall -g typescript".to_string(), berry_global))
+        );
+        assert_eq!(
+            translate("pnpm add -g typescript", Yarn, YarnDialect::Classic),
+            Some("yarn global add typescript".to_string())
+        );
+
+        // Updates
+        assert_eq!(
+            translate("npm update react", Yarn, YarnDialect::Classic),
+            Some("yarn upgrade react".to_string())
+        );
+        assert_eq!(
+            translate("pnpm up", Yarn, YarnDialect::Berry),
+            Some("yarn up".to_string())
+        );
+        assert_eq!(
+            translate("yarn up react", Npm, YarnDialect::Berry),
+            Some("npm update react".to_string())
+        );
+        assert_eq!(
+            translate("yarn upgrade react", Bun, YarnDialect::Classic),
+            Some("bun update react".to_string())
+        );
+
+        // Frozen installs
+        assert_eq!(
+            translate_text("npm ci", Yarn, YarnDialect::Berry),
+            Some("yarn install --immutable".to_string())
+        );
+
+        // Publishing
+        assert_eq!(
+            translate("npm publish --access public", Yarn, YarnDialect::Berry),
+            Some("yarn npm publish --access public".to_string())
+        );
+        assert_eq!(
+            translate("npm publish", Yarn, YarnDialect::Classic),
+            Some("yarn publish".to_string())
+        );
+        assert_eq!(
+            translate("yarn npm publish", Pnpm, YarnDialect::Berry),
+            Some("pnpm publish".to_string())
+        );
+    }
+
+    #[test]
+    fn test_create_translations() {
+        // npm create forwards options after `--`, the others take them directly
+        assert_eq!(
+            translate_text(
+                "npm create vite@latest my-app -- --template react",
+                Pnpm,
+                YarnDialect::Classic
+            ),
+            Some("pnpm create vite@latest my-app --template react".to_string())
+        );
+        assert_eq!(
+            translate_text(
+                "bun create vite my-app --template react",
+                Npm,
+                YarnDialect::Classic
+            ),
+            Some("npm create vite my-app -- --template react".to_string())
+        );
+
+        // npm init <initializer> is npm create
+        assert_eq!(
+            translate_text("npm init next-app", Pnpm, YarnDialect::Classic),
+            Some("pnpm create next-app".to_string())
+        );
+        assert_eq!(
+            translate_text("npm init @vitejs/app", Yarn, YarnDialect::Classic),
+            Some("yarn create @vitejs/app".to_string())
+        );
+
+        // The runner form and back
+        assert_eq!(
+            translate_text(
+                "npx create-next-app@latest my-app --ts",
+                Bun,
+                YarnDialect::Classic
+            ),
+            Some("bun create next-app@latest my-app --ts".to_string())
+        );
+        assert_eq!(
+            translate_text("npx -y @scope/create-app", Pnpm, YarnDialect::Classic),
+            Some("pnpm create @scope/app".to_string())
+        );
+        assert_eq!(
+            translate_text("yarn dlx create-vite", Npm, YarnDialect::Classic),
+            Some("npm create vite".to_string())
+        );
+        // Through the main entry point
+        assert_eq!(
+            translate_text("npm init next-app my-app", Pnpm, YarnDialect::Classic),
+            Some("pnpm create next-app my-app".to_string())
+        );
+        assert_eq!(
+            translate_text("npm init -y", Yarn, YarnDialect::Classic),
+            Some("yarn init -y".to_string())
+        );
+    }
+
+    #[test]
+    fn test_workspace_translations() {
+        // npm -w <path> / <name>
+        assert_eq!(
+            translate("npm install zod -w apps/web", Pnpm, YarnDialect::Classic),
+            Some("pnpm --filter ./apps/web add zod".to_string())
+        );
+        assert_eq!(
+            translate("npm run build --workspace=web", Yarn, YarnDialect::Classic),
+            Some("yarn workspace web run build".to_string())
+        );
+        assert_eq!(
+            translate("npm run test --workspaces", Pnpm, YarnDialect::Classic),
+            Some("pnpm -r run test".to_string())
+        );
+
+        // yarn workspace <name> / workspaces foreach
+        assert_eq!(
+            translate("yarn workspace web add zod", Npm, YarnDialect::Classic),
+            Some("npm install zod -w web".to_string())
+        );
+        assert_eq!(
+            translate("yarn workspace web build", Pnpm, YarnDialect::Berry),
+            Some("pnpm --filter web run build".to_string())
+        );
+        assert_eq!(
+            translate(
+                "yarn workspaces foreach -A run lint",
+                Npm,
+                YarnDialect::Berry
+            ),
+            Some("npm run lint --workspaces".to_string())
+        );
+
+        // pnpm --filter / -F / -r / -w
+        assert_eq!(
+            translate("pnpm --filter web add zod", Yarn, YarnDialect::Classic),
+            Some("yarn workspace web add zod".to_string())
+        );
+        assert_eq!(
+            translate("pnpm -F web -F docs build", Yarn, YarnDialect::Berry),
+            Some("yarn workspaces foreach -A --include web --include docs build".to_string())
+        );
+        assert_eq!(
+            translate("pnpm -r run build -- --watch", Yarn, YarnDialect::Classic),
+            Some("yarn workspaces run build --watch".to_string())
+        );
+        assert_eq!(
+            translate("pnpm add -w -D typescript", Yarn, YarnDialect::Classic),
+            Some("yarn add -D typescript -W".to_string())
+        );
+        assert_eq!(
+            translate("pnpm -w run build", Npm, YarnDialect::Classic),
+            Some("npm run build".to_string())
+        );
+
+        // bun --filter
+        assert_eq!(
+            translate("bun --filter web run dev", Pnpm, YarnDialect::Classic),
+            Some("pnpm --filter web run dev".to_string())
+        );
+        assert_eq!(
+            translate("pnpm -r run build", Bun, YarnDialect::Classic),
+            Some("bun --filter '*' run build".to_string())
+        );
+        assert_eq!(
+            translate("npm i zod -w packages/ui", Bun, YarnDialect::Classic),
+            Some("(cd packages/ui && bun add zod)".to_string())
+        );
+
+        // Options after a script name or an executed binary are theirs
+        assert_eq!(
+            translate("pnpm exec tsc -w", Npm, YarnDialect::Classic),
+            Some("npm exec -- tsc -w".to_string())
+        );
+        assert_eq!(
+            translate("pnpm run test -w", Npm, YarnDialect::Classic),
+            Some("npm run test -- -w".to_string())
+        );
+        assert_eq!(
+            translate("yarn build -W", Npm, YarnDialect::Classic),
+            Some("npm run build -- -W".to_string())
+        );
+        assert_eq!(
+            translate(
+                "pnpm -r --filter web run build -r",
+                Npm,
+                YarnDialect::Classic
+            ),
+            Some("npm run build -w web -- -r".to_string())
+        );
+
+        // Inexact cases still translate, with a warning
+        let warnings = |command: &str, to_pm, yarn_dialect| {
+            translate_simple_command(command, to_pm, yarn_dialect, &[])
+                .map(|result| (result.translated, result.warnings))
+        };
+        let every_workspace = |pm: &str| {
+            vec![format!(
+                "{} cannot run this command in every workspace at once, run it in each of them",
+                pm
+            )]
+        };
+        assert_eq!(
+            warnings("npm install zod --workspaces", Yarn, YarnDialect::Classic),
+            Some(("yarn add zod".to_string(), every_workspace("yarn")))
+        );
+        assert_eq!(
+            warnings("npm ci --workspaces", Yarn, YarnDialect::Classic),
+            Some((
+                "yarn install --frozen-lockfile".to_string(),
+                every_workspace("yarn")
+            ))
+        );
+        assert_eq!(
+            warnings("pnpm -r update", Yarn, YarnDialect::Classic),
+            Some(("yarn upgrade".to_string(), every_workspace("yarn")))
+        );
+        assert_eq!(
+            warnings("pnpm -r update", Yarn, YarnDialect::Berry),
+            Some(("yarn workspaces foreach -A up".to_string(), Vec::new()))
+        );
+        assert_eq!(
+            warnings("pnpm -r add zod", Deno, YarnDialect::Classic),
+            Some(("deno add npm:zod".to_string(), every_workspace("deno")))
+        );
+        assert_eq!(
+            warnings("pnpm add -w typescript", Npm, YarnDialect::Classic),
+            Some((
+                "npm install typescript".to_string(),
+                vec![
+                    "npm has no option for the workspace root, run it from the root of the project"
+                        .to_string()
+                ]
+            ))
+        );
+        assert_eq!(
+            warnings("npm i zod -w apps/web", Deno, YarnDialect::Classic),
+            Some((
+                "(cd apps/web && deno add npm:zod)".to_string(),
+                vec![
+                    "deno cannot add or remove dependencies of another workspace, ran it from apps/web"
+                        .to_string()
+                ]
+            ))
+        );
+        assert_eq!(
+            translate("npm i zod -w ui", Bun, YarnDialect::Classic),
+            Some("bun add zod".to_string())
+        );
+        assert_eq!(
+            translate("pnpm --filter web... run build", Npm, YarnDialect::Classic),
+            Some("npm run build -w web...".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bare_install_translations() {
+        assert_eq!(
+            translate("npm i", Yarn, YarnDialect::Classic),
+            Some("yarn install".to_string())
+        );
+        assert_eq!(
+            translate("npm install --legacy-peer-deps", Bun, YarnDialect::Classic),
+            Some("bun install --legacy-peer-deps".to_string())
+        );
+        assert_eq!(
+            translate(
+                "npm install --registry https://r.example",
+                Pnpm,
+                YarnDialect::Classic
+            ),
+            Some("pnpm install --registry https://r.example".to_string())
+        );
+        assert_eq!(
+            translate("pnpm i", Npm, YarnDialect::Classic),
+            Some("npm install".to_string())
+        );
+        assert_eq!(
+            translate_text("yarn", Bun, YarnDialect::Classic),
+            Some("bun install".to_string())
+        );
+
+        // Installs that name packages still become add
+        assert_eq!(
+            translate("npm install -D vitest", Pnpm, YarnDialect::Classic),
+            Some("pnpm add -D vitest".to_string())
+        );
+        assert_eq!(
+            translate("bun install react", Yarn, YarnDialect::Classic),
+            Some("yarn add react".to_string())
+        );
+    }
+
+    #[test]
+    fn test_frozen_install_translations() {
+        // npm ci -> frozen installs
+        assert_eq!(
+            translate_text("npm ci", Pnpm, YarnDialect::Classic),
+            Some("pnpm install --frozen-lockfile".to_string())
+        );
+        assert_eq!(
+            translate_text("npm ci", Yarn, YarnDialect::Classic),
+            Some("yarn install --frozen-lockfile".to_string())
+        );
+        assert_eq!(
+            translate_text(
+                "npm clean-install --ignore-scripts",
+                Bun,
+                YarnDialect::Classic
+            ),
+            Some("bun install --frozen-lockfile --ignore-scripts".to_string())
+        );
+
+        // Frozen installs -> npm ci
+        assert_eq!(
+            translate_text("pnpm install --frozen-lockfile", Npm, YarnDialect::Classic),
+            Some("npm ci".to_string())
+        );
+        assert_eq!(
+            translate_text("yarn install --immutable", Npm, YarnDialect::Classic),
+            Some("npm ci".to_string())
+        );
+        assert_eq!(
+            translate_text("yarn --frozen-lockfile", Bun, YarnDialect::Classic),
+            Some("bun install --frozen-lockfile".to_string())
+        );
+        assert_eq!(
+            translate_text("bun i --frozen-lockfile", Pnpm, YarnDialect::Classic),
+            Some("pnpm install --frozen-lockfile".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dependency_flag_translations() {
+        assert_eq!(
+            translate("yarn add --dev typescript", Npm, YarnDialect::Classic),
+            Some("npm install -D typescript".to_string())
+        );
+        assert_eq!(
+            translate("npm install --save-exact react", Yarn, YarnDialect::Classic),
+            Some("yarn add -E react".to_string())
+        );
+        assert_eq!(
+            translate("npm i --save-optional fsevents", Bun, YarnDialect::Classic),
+            Some("bun add --optional fsevents".to_string())
+        );
+        assert_eq!(
+            translate("bun add --optional fsevents", Pnpm, YarnDialect::Classic),
+            Some("pnpm add -O fsevents".to_string())
+        );
+        assert_eq!(
+            translate("npm i --save-peer react", Yarn, YarnDialect::Classic),
+            Some("yarn add -P react".to_string())
+        );
+
+        // yarn's -P means peer, npm's -P means prod
+        assert_eq!(
+            translate("yarn add -P react", Npm, YarnDialect::Classic),
+            Some("npm install --save-peer react".to_string())
+        );
+        assert_eq!(
+            translate("npm i -P react", Yarn, YarnDialect::Classic),
+            Some("yarn add react".to_string())
+        );
+
+        // Spellings the target also understands are kept as written
+        assert_eq!(
+            translate("npm i --save-dev vitest", Pnpm, YarnDialect::Classic),
+            Some("pnpm add --save-dev vitest".to_string())
+        );
+
+        // Flags without an equivalent are dropped rather than copied
+        assert_eq!(
+            translate("npm i --no-save left-pad", Pnpm, YarnDialect::Classic),
+            Some("pnpm add left-pad".to_string())
+        );
+
+        // Script arguments are never treated as dependency flags
+        assert_eq!(
+            translate("npm run test -- -D", Yarn, YarnDialect::Classic),
+            Some("yarn run test -D".to_string())
+        );
+    }
+
+    #[test]
+    fn test_quoted_arguments() {
+        // Quoted arguments keep their quoting instead of being split apart
+        assert_eq!(
+            translate(
+                r#"npm run lint -- --ext ".ts, .tsx""#,
+                Pnpm,
+                YarnDialect::Classic
+            ),
+            Some(r#"pnpm run lint --ext ".ts, .tsx""#.to_string())
+        );
+
+        // Escaped spaces and variables come through unchanged
+        assert_eq!(
+            translate(r"yarn add ./my\ lib $EXTRA", Npm, YarnDialect::Classic),
+            Some(r"npm install ./my\ lib $EXTRA".to_string())
+        );
+        assert_eq!(
+            translate("yarn 'my script' '$HOME'", Bun, YarnDialect::Classic),
+            Some("bun run 'my script' '$HOME'".to_string())
+        );
+
+        // Unbalanced quotes are not translated
+        assert_eq!(
+            translate("npm install 'react", Pnpm, YarnDialect::Classic),
+            None
+        );
+    }
+
+    #[test]
+    fn test_translate_command_integration() {
+        let translate_command =
+            |text: &str| super::translate(text, Pnpm, YarnDialect::Classic, &[]);
+
+        // Test runner translation
+        assert_eq!(
+            translate_command("npx create-react-app my-app").map(|result| result.translated),
+            Some("pnpm create react-app my-app".to_string())
+        );
+
+        // Test package manager translation
+        assert_eq!(
+            translate_command("npm install lodash").map(|result| result.translated),
+            Some("pnpm add lodash".to_string())
+        );
+
+        // Test yarn script translation
+        assert_eq!(
+            translate_command("yarn build").map(|result| result.translated),
+            Some("pnpm run build".to_string())
+        );
+
+        // Prompts are removed, environment assignments and wrappers kept
+        assert_eq!(
+            translate_command("$ npm i").map(|result| result.translated),
+            Some("pnpm install".to_string())
+        );
+        assert_eq!(
+            translate_command("NODE_ENV=production npm run build").map(|result| result.translated),
+            Some("NODE_ENV=production pnpm run build".to_string())
+        );
+        assert_eq!(
+            translate_command("sudo npm i -g yarn").map(|result| result.translated),
+            Some("sudo pnpm add -g yarn".to_string())
+        );
+        assert_eq!(
+            translate_command("> npx cross-env FOO=1 yarn test && time yarn build")
+                .map(|result| result.translated),
+            Some("npx cross-env FOO=1 pnpm run test && time pnpm run build".to_string())
+        );
+        assert_eq!(translate_command("$ pnpm dev"), None);
+
+        // Code blocks are translated line by line
+        assert_eq!(
+            translate_command(
+                "```bash\n# Set up\n$ npm install \\\n    react\nnpm run dev # then open localhost\n```\n"
+            )
+            .map(|result| result.translated),
+            Some(
+                "```bash\n# Set up\npnpm add \\\n    react\npnpm run dev # then open localhost\n```\n"
+                    .to_string()
+            )
+        );
+
+        assert_eq!(
+            translate_command("Run <code>npm install &lt;pkg&gt;</code> first.")
+                .map(|result| result.translated),
+            Some("Run <code>pnpm add &lt;pkg&gt;</code> first.".to_string())
+        );
+
+        // Copied prose isn't taken for commands
+        assert_eq!(
+            super::translate(
+                "yarn is a package manager. Install it!",
+                Npm,
+                YarnDialect::Classic,
+                &[]
+            ),
+            None
+        );
+        assert_eq!(
+            translate_command("To get started:\nnpm install the dependencies, then npm run dev."),
+            None
+        );
+
+        // Commands in inline code are translated, the prose around them kept
+        assert_eq!(
+            translate_command("Run `npm install zod` and then `$ npm run dev`.")
+                .map(|result| result.translated),
+            Some("Run `pnpm add zod` and then `pnpm run dev`.".to_string())
+        );
+
+        // Test chained commands
+        assert_eq!(
+            translate_command("npm install zod && npm run build").map(|result| result.translated),
+            Some("pnpm add zod && pnpm run build".to_string())
+        );
+        assert_eq!(
+            translate_command("(cd app && npm install react) || yarn build; npx tsc | cat")
+                .map(|result| result.translated),
+            Some("(cd app && pnpm add react) || pnpm run build; pnpx tsc | cat".to_string())
+        );
+        assert_eq!(
+            translate_command("cd app && ls -la | grep src").map(|result| result.translated),
+            None
+        );
+
+        // A change of directory stays with its own command
+        let to_bun = |text: &str| {
+            super::translate(text, Bun, YarnDialect::Classic, &[]).map(|result| result.translated)
+        };
+        assert_eq!(
+            to_bun("npm i zod -w apps/web && npm run build"),
+            Some("(cd apps/web && bun add zod) && bun run build".to_string())
+        );
+        assert_eq!(
+            to_bun("npm i zod -w apps/web || echo fail"),
+            Some("(cd apps/web && bun add zod) || echo fail".to_string())
+        );
+
+        // Redirections aren't arguments
+        assert_eq!(
+            translate_command("npm install > /dev/null 2>&1").map(|result| result.translated),
+            Some("pnpm install > /dev/null 2>&1".to_string())
+        );
+        assert_eq!(
+            super::translate("npm i react >log", Deno, YarnDialect::Classic, &[])
+                .map(|result| result.translated),
+            Some("deno add npm:react >log".to_string())
+        );
+        assert_eq!(
+            translate_command("npm ls 2> errors.txt --depth 0").map(|result| result.translated),
+            Some("pnpm list --depth 0 2> errors.txt".to_string())
+        );
+
+        // Results of chained commands are merged
+        assert_eq!(
+            translate_command("yarn add -T lodash && npm ls && pnpm build"),
+            Some(TranslationResult {
+                original: "yarn add -T lodash && npm ls && pnpm build".to_string(),
+                translated: "pnpm add lodash && pnpm list && pnpm build".to_string(),
+                from: vec![Yarn, Npm],
+                to: Pnpm,
+                rules: vec!["add".to_string(), "built-in rule `npm ls`".to_string()],
+                warnings: vec!["pnpm has no equivalent for -T, dropped it".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_translate_document() {
+        let translate_document = |text: &str| {
+            super::translate_document(text, Pnpm, YarnDialect::Classic, &[])
+                .map(|result| result.translated)
+        };
+
+        // Prompts in documents are kept
+        assert_eq!(
+            translate_document("```sh\n$ npm install react\n  > npx tsc\n```\n"),
+            Some("```sh\n$ pnpm add react\n  > pnpx tsc\n```\n".to_string())
+        );
+
+        // Headings are prose, with commands in their inline code
+        assert_eq!(
+            translate_document("# Install with `npm install zod`\n"),
+            Some("# Install with `pnpm add zod`\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_translation_result() {
+        let result = |command: &str, to_pm: PackageManager| {
+            translate_simple_command(command, to_pm, YarnDialect::Berry, &[])
+        };
+
+        assert_eq!(
+            result("yarn add -T lodash", Npm),
+            Some(TranslationResult {
+                original: "yarn add -T lodash".to_string(),
+                translated: "npm install lodash".to_string(),
+                from: vec![Yarn],
+                to: Npm,
+                rules: vec!["add".to_string()],
+                warnings: vec!["npm has no equivalent for -T, dropped it".to_string()],
+            })
+        );
+        assert_eq!(
+            result("npm audit fix", Pnpm).map(|result| result.rules),
+            Some(vec!["built-in rule `npm audit fix`".to_string()])
+        );
+        assert_eq!(
+            result("npm install -g typescript", Yarn).map(|result| result.rules),
+            Some(vec!["global add".to_string()])
+        );
+
+        // A command a rule knows but can't translate still goes through the regular translation
+        let audit = result("npm audit fix", Bun).unwrap();
+        assert_eq!(audit.translated, "bun audit fix");
+        assert_eq!(
+            audit.warnings,
+            vec!["bun has no equivalent for `npm audit fix`"]
+        );
+    }
+
+    #[test]
+    fn test_detect_package_manager() {
+        assert_eq!(detect_package_manager("npm i -D vitest"), Some(Npm));
+        assert_eq!(detect_package_manager("$ npx tsc"), Some(Npm));
+        assert_eq!(detect_package_manager("  bunx prettier ."), Some(Bun));
+        assert_eq!(
+            detect_package_manager("cd app && NODE_ENV=production yarn build"),
+            Some(Yarn)
+        );
+        // Commands only a rule knows still tell which package manager they are for
+        assert_eq!(detect_package_manager("pnpm store prune"), Some(Pnpm));
+        assert_eq!(detect_package_manager("deno task dev"), Some(Deno));
+        assert_eq!(detect_package_manager("make build"), None);
+        assert_eq!(detect_package_manager("npm install 'react"), None);
+    }
+}
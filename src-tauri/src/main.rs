@@ -3,6 +3,7 @@
 
 mod command;
 mod fns;
+mod settings;
 mod tray;
 
 use std::time::Duration;
@@ -10,11 +11,15 @@ use tauri::{Emitter, Manager};
 
 fn main() {
     tauri::Builder::default()
+        .manage(command::AppState::default())
         .invoke_handler(tauri::generate_handler![
             command::init,
             command::show_menubar_panel,
             command::set_preferred_package_manager,
             command::get_preferred_package_manager,
+            command::set_yarn_dialect,
+            command::get_yarn_dialect,
+            command::reload_rules,
             command::get_monitoring_state,
             command::toggle_monitoring,
             command::quit_app,
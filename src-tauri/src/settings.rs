@@ -0,0 +1,197 @@
+// User preferences, kept in `settings.json` in the app config dir so they survive restarts.
+//
+// The file records the schema version it was written with. To change the schema, add a
+// migration to `MIGRATIONS` that turns the previous version's JSON into the new one; older
+// files are migrated when they are loaded, and saved in the current version.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use packswitcher_core::{PackageManager, YarnDialect};
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub preferred_pm: PackageManager,
+    pub monitoring: bool,
+    pub yarn_dialect: YarnDialect,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            preferred_pm: PackageManager::Npm,
+            monitoring: false,
+            yarn_dialect: YarnDialect::Classic,
+        }
+    }
+}
+
+// Upgrades the settings of one schema version to the next
+type Migration = fn(&mut Map<String, Value>);
+
+// `MIGRATIONS[0]` turns version 1 into version 2, and so on
+const MIGRATIONS: &[Migration] = &[];
+
+pub const SETTINGS_VERSION: u64 = MIGRATIONS.len() as u64 + 1;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+// Loads the settings from `config_dir`. No file means the default settings.
+pub fn load_settings(config_dir: &Path) -> Result<Settings, String> {
+    let path = config_dir.join(SETTINGS_FILE);
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+
+    let text = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_settings(&text, MIGRATIONS).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn save_settings(config_dir: &Path, settings: &Settings) -> Result<(), String> {
+    let path = config_dir.join(SETTINGS_FILE);
+    fs::create_dir_all(config_dir)
+        .map_err(|e| format!("Failed to create {}: {}", config_dir.display(), e))?;
+
+    // Write to a temporary file first, so a crash can't leave half a settings file behind
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, settings_to_json(settings))
+        .and_then(|_| fs::rename(&temp_path, &path))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn settings_to_json(settings: &Settings) -> String {
+    let mut object = Map::new();
+    object.insert("version".to_string(), SETTINGS_VERSION.into());
+    if let Ok(Value::Object(fields)) = serde_json::to_value(settings) {
+        object.extend(fields);
+    }
+    serde_json::to_string_pretty(&object).unwrap()
+}
+
+// Reads settings written with any schema version up to the one `migrations` lead to.
+// Missing fields get their default value.
+fn parse_settings(text: &str, migrations: &[Migration]) -> Result<Settings, String> {
+    let mut value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let object = value
+        .as_object_mut()
+        .ok_or("the settings are not a JSON object")?;
+
+    let current_version = migrations.len() as u64 + 1;
+    let version = match object.remove("version") {
+        Some(version) => version.as_u64().ok_or("the version is not a number")?,
+        None => return Err("the settings have no version".to_string()),
+    };
+    if version == 0 || version > current_version {
+        return Err(format!(
+            "version {} is not supported, expected at most version {}",
+            version, current_version
+        ));
+    }
+
+    for migration in &migrations[version as usize - 1..] {
+        migration(object);
+    }
+
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_settings() {
+        assert_eq!(
+            parse_settings(
+                r#"{ "version": 1, "preferred_pm": "pnpm", "monitoring": true }"#,
+                MIGRATIONS
+            ),
+            Ok(Settings {
+                preferred_pm: PackageManager::Pnpm,
+                monitoring: true,
+                yarn_dialect: YarnDialect::Classic,
+            })
+        );
+
+        let settings = Settings {
+            preferred_pm: PackageManager::Deno,
+            monitoring: false,
+            yarn_dialect: YarnDialect::Berry,
+        };
+        assert_eq!(
+            parse_settings(&settings_to_json(&settings), MIGRATIONS),
+            Ok(settings)
+        );
+
+        assert!(parse_settings(r#"{ "preferred_pm": "pnpm" }"#, MIGRATIONS).is_err());
+        assert_eq!(
+            parse_settings(r#"{ "version": 2 }"#, MIGRATIONS),
+            Err("version 2 is not supported, expected at most version 1".to_string())
+        );
+        assert!(
+            parse_settings(r#"{ "version": 1, "preferred_pm": "pip" }"#, MIGRATIONS)
+                .unwrap_err()
+                .contains("unknown variant `pip`")
+        );
+    }
+
+    #[test]
+    fn test_migrations() {
+        // A version 2 that renamed `pm` to `preferred_pm`, and a version 3 that turned
+        // `yarn_berry` into `yarn_dialect`
+        let migrations: &[Migration] = &[
+            |settings| {
+                if let Some(pm) = settings.remove("pm") {
+                    settings.insert("preferred_pm".to_string(), pm);
+                }
+            },
+            |settings| {
+                if let Some(Value::Bool(berry)) = settings.remove("yarn_berry") {
+                    let dialect = if berry { "berry" } else { "classic" };
+                    settings.insert("yarn_dialect".to_string(), dialect.into());
+                }
+            },
+        ];
+
+        let expected = Ok(Settings {
+            preferred_pm: PackageManager::Yarn,
+            monitoring: true,
+            yarn_dialect: YarnDialect::Berry,
+        });
+        assert_eq!(
+            parse_settings(
+                r#"{ "version": 1, "pm": "yarn", "monitoring": true, "yarn_berry": true }"#,
+                migrations
+            ),
+            expected
+        );
+        assert_eq!(
+            parse_settings(
+                r#"{ "version": 2, "preferred_pm": "yarn", "monitoring": true, "yarn_berry": true }"#,
+                migrations
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_load_and_save_settings() {
+        let dir =
+            std::env::temp_dir().join(format!("packswitcher-settings-{}", std::process::id()));
+
+        assert_eq!(load_settings(&dir), Ok(Settings::default()));
+
+        let settings = Settings {
+            preferred_pm: PackageManager::Bun,
+            ..Settings::default()
+        };
+        save_settings(&dir, &settings).unwrap();
+        assert_eq!(load_settings(&dir), Ok(settings));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,18 +1,37 @@
 import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
 import React, { useEffect, useState } from 'react'
 import { UpdateChecker } from './UpdateChecker'
-import type { AsyncFunction, PackageManager } from './types'
+import {
+  isUnknownPackageManagerError,
+  type AsyncFunction,
+  type PackageManager,
+  type TranslationResult,
+  type YarnDialect,
+} from './types'
 
 import './App.css'
 
+const YARN_DIALECT_LABELS: Record<YarnDialect, string> = {
+  classic: 'v1',
+  berry: 'v2+',
+}
+
 function App(): React.JSX.Element {
   const [selectedPM, setSelectedPM] = useState<PackageManager>('npm')
+  const [yarnDialect, setYarnDialect] = useState<YarnDialect>('classic')
   const [isMonitoring, setIsMonitoring] = useState<boolean>(false)
+  const [lastTranslation, setLastTranslation] =
+    useState<TranslationResult | null>(null)
+  // Why the backend rejected the last package manager chosen
+  const [pmError, setPMError] = useState<string | null>(null)
+  // Why the custom rules file couldn't be loaded
+  const [rulesError, setRulesError] = useState<string | null>(null)
 
   useEffect((): void => {
     const initializeApp = async (): Promise<void> => {
       try {
-        await invoke('init')
+        setRulesError(await invoke<string | null>('init'))
 
         // Load saved preferences
         const pm = await invoke<PackageManager>('get_preferred_package_manager')
@@ -20,6 +39,9 @@ function App(): React.JSX.Element {
           setSelectedPM(pm)
         }
 
+        const dialect = await invoke<YarnDialect>('get_yarn_dialect')
+        setYarnDialect(dialect)
+
         const monitoringState = await invoke<boolean>('get_monitoring_state')
         setIsMonitoring(monitoringState)
       } catch (error) {
@@ -30,19 +52,62 @@ function App(): React.JSX.Element {
     initializeApp()
   }, [])
 
+  useEffect(() => {
+    let unlisten: UnlistenFn | undefined
+
+    // Show the latest translation, with anything that didn't translate exactly
+    const setupListener = async (): Promise<void> => {
+      try {
+        unlisten = await listen<TranslationResult>(
+          'command-translated',
+          (event): void => {
+            setLastTranslation(event.payload)
+          }
+        )
+      } catch (error) {
+        console.error('Failed to setup translation listener:', error)
+      }
+    }
+
+    setupListener()
+
+    return (): void => {
+      unlisten?.()
+    }
+  }, [])
+
   const handlePMChange: AsyncFunction<[PackageManager]> = async (
     pm: PackageManager
   ): Promise<void> => {
     try {
       setSelectedPM(pm)
       await invoke('set_preferred_package_manager', { packageManager: pm })
+      setPMError(null)
     } catch (error) {
       console.error('Failed to set preferred package manager:', error)
+      setPMError(
+        isUnknownPackageManagerError(error)
+          ? `Unknown package manager "${error.value}", expected one of ${error.expected.join(', ')}`
+          : String(error)
+      )
       // Revert the UI state if the backend call failed
       setSelectedPM(selectedPM)
     }
   }
 
+  const handleYarnDialectChange: AsyncFunction<[YarnDialect]> = async (
+    dialect: YarnDialect
+  ): Promise<void> => {
+    try {
+      setYarnDialect(dialect)
+      await invoke('set_yarn_dialect', { dialect })
+    } catch (error) {
+      console.error('Failed to set yarn dialect:', error)
+      // Revert the UI state if the backend call failed
+      setYarnDialect(yarnDialect)
+    }
+  }
+
   const toggleMonitoring = async (): Promise<void> => {
     const newState = !isMonitoring
 
@@ -56,6 +121,16 @@ function App(): React.JSX.Element {
     }
   }
 
+  // Loads the custom rules file again after it was edited
+  const reloadRules: AsyncFunction = async (): Promise<void> => {
+    try {
+      await invoke<number>('reload_rules')
+      setRulesError(null)
+    } catch (error) {
+      setRulesError(String(error))
+    }
+  }
+
   const handleQuit: AsyncFunction = async (): Promise<void> => {
     try {
       await invoke('quit_app')
@@ -93,7 +168,7 @@ function App(): React.JSX.Element {
           <div className="pm-selector">
             <label>Preferred Package Manager:</label>
             <div className="pm-buttons">
-              {(['npm', 'pnpm', 'yarn', 'bun'] as const).map(
+              {(['npm', 'pnpm', 'yarn', 'bun', 'deno'] as const).map(
                 (pm: PackageManager) => (
                   <button
                     key={pm}
@@ -105,14 +180,55 @@ function App(): React.JSX.Element {
                 )
               )}
             </div>
+            {pmError && <div className="pm-error">⚠️ {pmError}</div>}
           </div>
 
+          {selectedPM === 'yarn' && (
+            <div className="pm-selector">
+              <label>Yarn Version:</label>
+              <div className="pm-buttons">
+                {(['classic', 'berry'] as const).map(
+                  (dialect: YarnDialect) => (
+                    <button
+                      key={dialect}
+                      className={`pm-button ${yarnDialect === dialect ? 'selected' : ''}`}
+                      onClick={() => handleYarnDialectChange(dialect)}
+                    >
+                      {YARN_DIALECT_LABELS[dialect]}
+                    </button>
+                  )
+                )}
+              </div>
+            </div>
+          )}
+
           <button
             className={`monitor-toggle ${isMonitoring ? 'stop' : 'start'}`}
             onClick={toggleMonitoring}
           >
             {isMonitoring ? 'Stop Monitoring' : 'Start Monitoring'}
           </button>
+
+          <div className="rules">
+            <button className="reload-rules-button" onClick={reloadRules}>
+              Reload Custom Rules
+            </button>
+            {rulesError && (
+              <div className="rules-error">⚠️ {rulesError}</div>
+            )}
+          </div>
+
+          {lastTranslation && (
+            <div className="last-translation">
+              <label>Last Translation:</label>
+              <code>{lastTranslation.translated}</code>
+              {lastTranslation.warnings.map((warning: string) => (
+                <div key={warning} className="translation-warning">
+                  ⚠️ {warning}
+                </div>
+              ))}
+            </div>
+          )}
         </div>
       </div>
     </>
@@ -1,5 +1,34 @@
 // Package manager types
-export type PackageManager = 'npm' | 'pnpm' | 'yarn' | 'bun'
+export type PackageManager = 'npm' | 'pnpm' | 'yarn' | 'bun' | 'deno'
+
+// Error returned by set_preferred_package_manager for unknown names
+export interface UnknownPackageManagerError {
+  value: string
+  expected: PackageManager[]
+}
+
+export const isUnknownPackageManagerError = (
+  error: unknown
+): error is UnknownPackageManagerError =>
+  typeof error === 'object' &&
+  error !== null &&
+  'value' in error &&
+  'expected' in error
+
+// Yarn 1 (classic) or Yarn 2+ (berry)
+export type YarnDialect = 'classic' | 'berry'
+
+// Payload of the command-translated event
+export interface TranslationResult {
+  original: string
+  translated: string
+  from: PackageManager[]
+  to: PackageManager
+  // The operations or rules that produced the translation
+  rules: string[]
+  // Parts of the command that could not be translated exactly
+  warnings: string[]
+}
 
 // Update-related types
 export interface UpdateInfo {